@@ -27,9 +27,16 @@ impl ReceiveThread {
                 biased;
                 packet = self.socket_rx.receive_packet() => {
                     if let Ok(packet) = packet {
-                        let packet_type = PacketType::parse_packet_type(&packet.clone_id_byte());
+                        let packet_type = match PacketType::parse_packet_type(packet.as_id_byte()) {
+                            Ok(packet_type) => packet_type,
+                            Err(entry) => {
+                                logging_warning!(entry);
+                                continue;
+                            }
+                        };
                         let result = match packet_type {
                             PacketType::AgentInfoAckPacket => self.receiver_tx.agent_info_ack_packet.send(packet),
+                            PacketType::AgentInfoRejectPacket => self.receiver_tx.agent_info_reject_packet.send(packet),
                             PacketType::ControlPacket => self.receiver_tx.control_packet.send(packet),
                             PacketType::DataChannelPortPacket => self.receiver_tx.data_channel_port_packet.send(packet),
                             PacketType::PerformanceAckPacket => self.receiver_tx.performance_ack_packet.send(packet),
@@ -8,6 +8,7 @@ use tokio::sync::oneshot;
 
 pub struct ReceiverTX {
     pub agent_info_ack_packet: mpsc::UnboundedSender<BasePacket>,
+    pub agent_info_reject_packet: mpsc::UnboundedSender<BasePacket>,
     pub control_packet: mpsc::UnboundedSender<BasePacket>,
     pub data_channel_port_packet: mpsc::UnboundedSender<BasePacket>,
     pub performance_ack_packet: mpsc::UnboundedSender<BasePacket>,
@@ -16,6 +17,7 @@ pub struct ReceiverTX {
 pub struct ControlChannelReceiver {
     stop_signal_tx: Option<oneshot::Sender<()>>,
     pub agent_info_ack_packet: mpsc::UnboundedReceiver<BasePacket>,
+    pub agent_info_reject_packet: mpsc::UnboundedReceiver<BasePacket>,
     pub control_packet: mpsc::UnboundedReceiver<BasePacket>,
     pub data_channel_port_packet: mpsc::UnboundedReceiver<BasePacket>,
     pub performance_ack_packet: mpsc::UnboundedReceiver<BasePacket>,
@@ -23,13 +25,14 @@ pub struct ControlChannelReceiver {
 
 impl ControlChannelReceiver {
     pub fn new(socket_rx: ReadHalf) -> Self {
-        create_unbounded_channels!(4);
+        create_unbounded_channels!(5);
         let (stop_signal_tx, stop_signal_rx) = oneshot::channel();
         let receiver_tx = ReceiverTX {
             agent_info_ack_packet: channel_0_tx,
-            control_packet: channel_1_tx,
-            data_channel_port_packet: channel_2_tx,
-            performance_ack_packet: channel_3_tx,
+            agent_info_reject_packet: channel_1_tx,
+            control_packet: channel_2_tx,
+            data_channel_port_packet: channel_3_tx,
+            performance_ack_packet: channel_4_tx,
         };
         let mut receive_thread = ReceiveThread::new(socket_rx, receiver_tx, stop_signal_rx);
         tokio::spawn(async move {
@@ -38,14 +41,16 @@ impl ControlChannelReceiver {
         Self {
             stop_signal_tx: Some(stop_signal_tx),
             agent_info_ack_packet: channel_0_rx,
-            control_packet: channel_1_rx,
-            data_channel_port_packet: channel_2_rx,
-            performance_ack_packet: channel_3_rx,
+            agent_info_reject_packet: channel_1_rx,
+            control_packet: channel_2_rx,
+            data_channel_port_packet: channel_3_rx,
+            performance_ack_packet: channel_4_rx,
         }
     }
 
     pub async fn disconnect(&mut self) {
         self.agent_info_ack_packet.close();
+        self.agent_info_reject_packet.close();
         self.control_packet.close();
         self.data_channel_port_packet.close();
         self.performance_ack_packet.close();
@@ -27,9 +27,16 @@ impl ReceiveThread {
                 biased;
                 packet = self.socket_rx.receive_packet() => {
                     if let Ok(packet) = packet {
-                        let packet_type = PacketType::parse_packet_type(&packet.clone_id_byte());
+                        let packet_type = match PacketType::parse_packet_type(packet.as_id_byte()) {
+                            Ok(packet_type) => packet_type,
+                            Err(entry) => {
+                                logging_warning!(entry);
+                                continue;
+                            }
+                        };
                         let result = match packet_type {
                             PacketType::AlivePacket => self.receiver_tx.alive_packet.send(packet),
+                            PacketType::CancelTaskPacket => self.receiver_tx.cancel_task_packet.send(packet),
                             PacketType::FileBodyPacket => self.receiver_tx.file_body_packet.send(packet),
                             PacketType::FileHeaderAckPacket => self.receiver_tx.file_header_ack_packet.send(packet),
                             PacketType::FileHeaderPacket => self.receiver_tx.file_header_packet.send(packet),
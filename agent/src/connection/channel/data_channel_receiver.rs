@@ -8,6 +8,7 @@ use tokio::sync::oneshot;
 
 pub struct ReceiverTX {
     pub alive_packet: UnboundedSender<BasePacket>,
+    pub cancel_task_packet: UnboundedSender<BasePacket>,
     pub file_body_packet: UnboundedSender<BasePacket>,
     pub file_header_ack_packet: UnboundedSender<BasePacket>,
     pub file_header_packet: UnboundedSender<BasePacket>,
@@ -21,6 +22,7 @@ pub struct ReceiverTX {
 pub struct DataChannelReceiver {
     stop_signal_tx: Option<oneshot::Sender<()>>,
     pub alive_packet: UnboundedReceiver<BasePacket>,
+    pub cancel_task_packet: UnboundedReceiver<BasePacket>,
     pub file_body_packet: UnboundedReceiver<BasePacket>,
     pub file_header_ack_packet: UnboundedReceiver<BasePacket>,
     pub file_header_packet: UnboundedReceiver<BasePacket>,
@@ -33,18 +35,19 @@ pub struct DataChannelReceiver {
 
 impl DataChannelReceiver {
     pub fn new(socket_rx: ReadHalf) -> Self {
-        create_unbounded_channels!(9);
+        create_unbounded_channels!(10);
         let (stop_signal_tx, stop_signal_rx) = oneshot::channel();
         let receiver_tx = ReceiverTX {
             alive_packet: channel_0_tx,
-            file_body_packet: channel_1_tx,
-            file_header_ack_packet: channel_2_tx,
-            file_header_packet: channel_3_tx,
-            file_transfer_end_packet: channel_4_tx,
-            file_transfer_result_packet: channel_5_tx,
-            still_process_packet: channel_6_tx,
-            task_info_packet: channel_7_tx,
-            task_result_ack_packet: channel_8_tx,
+            cancel_task_packet: channel_1_tx,
+            file_body_packet: channel_2_tx,
+            file_header_ack_packet: channel_3_tx,
+            file_header_packet: channel_4_tx,
+            file_transfer_end_packet: channel_5_tx,
+            file_transfer_result_packet: channel_6_tx,
+            still_process_packet: channel_7_tx,
+            task_info_packet: channel_8_tx,
+            task_result_ack_packet: channel_9_tx,
         };
         let mut receive_thread = ReceiveThread::new(socket_rx, receiver_tx, stop_signal_rx);
         tokio::spawn(async move {
@@ -53,19 +56,21 @@ impl DataChannelReceiver {
         Self {
             stop_signal_tx: Some(stop_signal_tx),
             alive_packet: channel_0_rx,
-            file_body_packet: channel_1_rx,
-            file_header_ack_packet: channel_2_rx,
-            file_header_packet: channel_3_rx,
-            file_transfer_end_packet: channel_4_rx,
-            file_transfer_result_packet: channel_5_rx,
-            still_process_packet: channel_6_rx,
-            task_info_packet: channel_7_rx,
-            task_result_ack_packet: channel_8_rx,
+            cancel_task_packet: channel_1_rx,
+            file_body_packet: channel_2_rx,
+            file_header_ack_packet: channel_3_rx,
+            file_header_packet: channel_4_rx,
+            file_transfer_end_packet: channel_5_rx,
+            file_transfer_result_packet: channel_6_rx,
+            still_process_packet: channel_7_rx,
+            task_info_packet: channel_8_rx,
+            task_result_ack_packet: channel_9_rx,
         }
     }
 
     pub async fn disconnect(&mut self) {
         self.alive_packet.close();
+        self.cancel_task_packet.close();
         self.file_body_packet.close();
         self.file_header_ack_packet.close();
         self.file_header_packet.close();
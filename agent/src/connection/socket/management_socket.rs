@@ -1,21 +1,24 @@
 use crate::connection::socket::socket_stream::SocketStream;
 use crate::utils::config::Config;
+use crate::utils::logging::*;
 use std::net::SocketAddr;
+use std::time::Duration;
 use tokio::net::TcpStream;
+use tokio::time::timeout;
 
 pub struct ManagementSocket;
 
 impl ManagementSocket {
-    pub async fn get_connection() -> (SocketStream, SocketAddr) {
+    pub async fn get_connection() -> Result<(SocketStream, SocketAddr), LogEntry> {
         let config = Config::now().await;
         let address = format!("{}:{}", config.management_address, config.management_port);
-        loop {
-            if let Ok(tcp_stream) = TcpStream::connect(&address).await {
-                if let Ok(socket_address) = tcp_stream.peer_addr() {
-                    let socket_stream = SocketStream::new(tcp_stream);
-                    break (socket_stream, socket_address);
-                }
-            }
-        }
+        let timeout_duration = Duration::from_secs(config.control_channel_timeout);
+        let tcp_stream = timeout(timeout_duration, TcpStream::connect(&address)).await
+            .map_err(|_| warning_entry!(NetworkEntry::ControlChannelTimeout))?
+            .map_err(|err| warning_entry!(NetworkEntry::EstablishConnectionError(err)))?;
+        let socket_address = tcp_stream.peer_addr()
+            .map_err(|err| warning_entry!(NetworkEntry::EstablishConnectionError(err)))?;
+        let socket_stream = config.tls.connect(tcp_stream, &config.management_address, timeout_duration).await?;
+        Ok((socket_stream, socket_address))
     }
 }
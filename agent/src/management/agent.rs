@@ -4,6 +4,7 @@ use crate::connection::channel::data_channel_receiver::DataChannelReceiver;
 use crate::connection::channel::data_channel_sender::DataChannelSender;
 use crate::connection::channel::{ControlChannel, DataChannel};
 use crate::connection::packet::agent_info_packet::AgentInfoPacket;
+use crate::connection::packet::agent_info_reject_packet::AgentInfoRejectPacket;
 use crate::connection::packet::alive_ack_packet::AliveAckPacket;
 use crate::connection::packet::control_ack_packet::ControlAckPacket;
 use crate::connection::packet::file_body_packet::FileBodyPacket;
@@ -17,34 +18,48 @@ use crate::connection::packet::task_info_ack_packet::TaskInfoAckPacket;
 use crate::connection::packet::Packet;
 use crate::connection::socket::socket_stream::SocketStream;
 use crate::management::inference_manager::InferenceManager;
+use crate::management::model_cache::ModelCache;
 use crate::management::monitor::Monitor;
+use crate::management::resource_sampler::ResourceSampler;
+use crate::management::utils::agent_registration::AgentRegistration;
 use crate::management::utils::agent_state::AgentState;
+use crate::management::utils::capability::{Capability, Negotiation, TRANSFER_WINDOW_SIZE};
 use crate::management::utils::file_header::FileHeader;
+use crate::management::utils::file_reader::FileReader;
 use crate::management::utils::file_transfer_result::FileTransferResult;
-use crate::management::utils::inference_argument::ModelType;
+use crate::management::utils::file_writer::FileWriter;
+use crate::management::utils::resource_usage::ResourceUsage;
 use crate::management::utils::task_result::TaskResult;
 use crate::management::utils::task_info::TaskInfo;
 use crate::utils::clear_unbounded_channel;
 use crate::utils::config::Config;
 use crate::utils::logging::*;
-use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::ffi::OsStr;
 use std::mem;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::fs::File;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::fs;
 use tokio::net::TcpStream;
 use tokio::select;
 use tokio::sync::RwLock;
-use tokio::time::{sleep, Instant};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout, Instant};
 use uuid::Uuid;
 use crate::connection::packet::still_process_ack_packet::StillProcessAckPacket;
 
+//Resource usage is missing when it could not be measured for the subtask alone.
+type InferenceResult = Result<Option<ResourceUsage>, LogEntry>;
+
 pub struct Agent {
     pub state: AgentState,
-    previous_task_uuid: Option<Uuid>,
+    negotiation: Negotiation,
+    concurrency: usize,
+    devices: Vec<Option<u32>>,
+    pending_inference: VecDeque<TaskInfo>,
+    running_inference: Vec<(TaskInfo, JoinHandle<InferenceResult>)>,
+    completed_inference: VecDeque<(TaskInfo, InferenceResult)>,
     control_channel_sender: ControlChannelSender,
     control_channel_receiver: ControlChannelReceiver,
     data_channel_sender: Option<DataChannelSender>,
@@ -54,8 +69,25 @@ pub struct Agent {
 impl Agent {
     pub async fn new(socket_stream: SocketStream) -> Result<Self, LogEntry> {
         let config = Config::now().await;
-        let mut information_confirm = false;
-        let information = serde_json::to_vec(&Monitor::get_system_info().await)
+        let mut negotiation: Option<Negotiation> = None;
+        let capability = Self::capability().await;
+        let mut information = Monitor::get_system_info().await;
+        //One worker pool per GPU device, agents without a GPU run a single pool without pinning.
+        let mut devices: Vec<Option<u32>> = information.gpus.iter()
+            .map(|gpu_device| Some(gpu_device.index))
+            .collect();
+        if devices.is_empty() {
+            devices.push(None);
+        }
+        information.backends = InferenceManager::capabilities().await;
+        let registration = AgentRegistration {
+            token: config.token.clone(),
+            information,
+            capability: capability.clone(),
+            cached_models: ModelCache::hashes().await,
+            concurrency: config.inference_concurrency,
+        };
+        let information = serde_json::to_vec(&registration)
             .map_err(|err| error_entry!(IOEntry::SerdeSerializeError(err)))?;
         let (mut control_channel_sender, mut control_channel_receiver) = ControlChannel::new(socket_stream);
         let timer = Instant::now();
@@ -64,7 +96,7 @@ impl Agent {
         let timeout_duration = Duration::from_secs(config.control_channel_timeout);
         while timer.elapsed() <= timeout_duration {
             if timer.elapsed() > polling_times * polling_interval {
-                if !information_confirm {
+                if negotiation.is_none() {
                     control_channel_sender.send(AgentInfoPacket::new(information.clone())).await;
                 } else {
                     let performance = Monitor::get_performance().await;
@@ -77,17 +109,37 @@ impl Agent {
             select! {
                 biased;
                 packet = control_channel_receiver.agent_info_ack_packet.recv() => {
-                    let _ = packet.ok_or(information_entry!(NetworkEntry::ChannelClosed))?;
-                    information_confirm = true;
+                    let packet = packet.ok_or(information_entry!(NetworkEntry::ChannelClosed))?;
+                    let management_capability = serde_json::from_slice::<Capability>(packet.as_data_byte())
+                        .map_err(|err| error_entry!(IOEntry::SerdeDeserializeError(err)))?;
+                    match capability.negotiate(&management_capability) {
+                        Ok(result) => negotiation = Some(result),
+                        Err(reason) => {
+                            //Management is told why, instead of waiting for the registration to time out.
+                            let entry = NetworkEntry::IncompatiblePeer(reason);
+                            control_channel_sender.send(AgentInfoRejectPacket::new(entry.to_string().into_bytes())).await;
+                            control_channel_sender.disconnect().await;
+                            control_channel_receiver.disconnect().await;
+                            Err(warning_entry!(entry))?;
+                        }
+                    }
+                }
+                packet = control_channel_receiver.agent_info_reject_packet.recv() => {
+                    let packet = packet.ok_or(information_entry!(NetworkEntry::ChannelClosed))?;
+                    Err(warning_entry!(NetworkEntry::RegistrationRejected(packet.data_to_string())))?;
                 }
                 packet = control_channel_receiver.performance_ack_packet.recv() => {
                     let _ = packet.ok_or(information_entry!(NetworkEntry::ChannelClosed))?;
-                    if !information_confirm {
-                        Err(error_entry!(MiscEntry::WrongDeliverOrder))?;
-                    }
+                    let negotiation = negotiation
+                        .ok_or(error_entry!(MiscEntry::WrongDeliverOrder))?;
                     let agent = Self {
                         state: AgentState::None,
-                        previous_task_uuid: None,
+                        negotiation,
+                        concurrency: config.inference_concurrency,
+                        devices: devices.clone(),
+                        pending_inference: VecDeque::new(),
+                        running_inference: Vec::new(),
+                        completed_inference: VecDeque::new(),
                         control_channel_sender,
                         control_channel_receiver,
                         data_channel_sender: None,
@@ -101,6 +153,14 @@ impl Agent {
         Err(information_entry!(NetworkEntry::ControlChannelTimeout))
     }
 
+    async fn capability() -> Capability {
+        let model_types = InferenceManager::capabilities().await
+            .into_iter()
+            .map(|backend_capability| backend_capability.model_type)
+            .collect();
+        Capability::new(model_types)
+    }
+
     pub async fn run(agent: Arc<RwLock<Agent>>) {
         let for_performance = agent.clone();
         let for_management = agent;
@@ -115,6 +175,7 @@ impl Agent {
     pub async fn terminate(agent: &Arc<RwLock<Agent>>) {
         logging_information!(SystemEntry::Terminating);
         let mut agent = agent.write().await;
+        agent.abort_inference().await;
         agent.control_channel_sender.disconnect().await;
         agent.control_channel_receiver.disconnect().await;
         if let Some(data_channel_sender) = &mut agent.data_channel_sender {
@@ -176,6 +237,7 @@ impl Agent {
             let state = agent.read().await.state;
             let result = match state {
                 AgentState::ProcessTask => Self::process_task(&agent).await,
+                AgentState::CollectResult => Self::collect_result(&agent).await,
                 AgentState::Idle(idle_time) => Self::idle(&agent, Duration::from_secs(idle_time)).await,
                 AgentState::CreateDataChannel => Self::create_data_channel(&agent).await,
                 AgentState::Terminate => {
@@ -232,32 +294,98 @@ impl Agent {
 
     async fn process_task(agent: &Arc<RwLock<Agent>>) -> Result<(), LogEntry> {
         let task_info = Self::receive_task(agent).await?;
-        let result = Self::waiting_inference(agent, &task_info).await
-            .map_err(|err| err.message);
-        let task_result = TaskResult::new(result);
+        let mut agent = agent.write().await;
+        agent.pending_inference.push_back(task_info);
+        agent.start_inference().await;
+        Ok(())
+    }
+
+    async fn collect_result(agent: &Arc<RwLock<Agent>>) -> Result<(), LogEntry> {
+        let (task_info, result) = Self::waiting_inference(agent).await?;
+        let report = Self::report_result(agent, &task_info, result).await;
+        Self::release_task(&task_info).await;
+        report
+    }
+
+    async fn report_result(agent: &Arc<RwLock<Agent>>, task_info: &TaskInfo, result: InferenceResult) -> Result<(), LogEntry> {
+        let success = result.is_ok();
+        let task_result = match result {
+            Ok(resource_usage) => TaskResult::new(task_info.subtask_uuid, Ok(()), String::new(), resource_usage),
+            Err(entry) => TaskResult::new(task_info.subtask_uuid, Err(entry.message), entry.debug_info, None),
+        };
         Self::notice_complete(agent, &task_result).await?;
-        Self::transfer_result(agent, &task_info).await?;
+        if success {
+            Self::transfer_result(agent, task_info).await?;
+        }
         Ok(())
     }
 
     async fn receive_task(agent: &Arc<RwLock<Agent>>) -> Result<TaskInfo, LogEntry> {
-        let task_info = Self::receive_task_info(agent).await?;
-        let previous_task_uuid = agent.read().await.previous_task_uuid;
-        let need_receive_model = if let Some(previous_task_uuid) = previous_task_uuid {
-            previous_task_uuid != task_info.uuid
-        } else {
-            true
-        };
-        if need_receive_model {
-            let model_folder = PathBuf::from("./SavedModel");
-            Self::receive_file(agent, &model_folder).await?;
-            agent.write().await.previous_task_uuid = Some(task_info.uuid);
+        let mut task_info = Self::receive_task_info(agent).await?;
+        if let Err(entry) = Self::receive_task_file(agent, &mut task_info).await {
+            Self::release_task(&task_info).await;
+            Err(entry)?;
         }
-        let media_folder = PathBuf::from("./SavedFile");
-        Self::receive_file(agent, &media_folder).await?;
         Ok(task_info)
     }
 
+    async fn receive_task_file(agent: &Arc<RwLock<Agent>>, task_info: &mut TaskInfo) -> Result<(), LogEntry> {
+        for folder in [Self::media_folder(task_info), Self::result_folder(task_info)] {
+            fs::create_dir_all(&folder).await
+                .map_err(|err| error_entry!(IOEntry::CreateDirectoryError(folder.display(), err)))?;
+        }
+        if task_info.model_path.is_none() {
+            let model_folder = Self::model_folder(task_info);
+            fs::create_dir_all(&model_folder).await
+                .map_err(|err| error_entry!(IOEntry::CreateDirectoryError(model_folder.display(), err)))?;
+            let model_path = Self::receive_file(agent, &model_folder).await?;
+            task_info.model_path = if task_info.model_hash.is_empty() {
+                Some(model_path)
+            } else {
+                Some(ModelCache::insert(&task_info.model_hash, &model_path).await?)
+            };
+        }
+        Self::receive_file(agent, &Self::media_folder(task_info)).await?;
+        Ok(())
+    }
+
+    //Every subtask keeps its files in folders named by its subtask uuid, so concurrent subtasks never share a path.
+    fn model_folder(task_info: &TaskInfo) -> PathBuf {
+        #[cfg(target_os = "linux")]
+        let folder = PathBuf::from(format!("./SavedModel/{}", task_info.subtask_uuid));
+        #[cfg(target_os = "windows")]
+        let folder = PathBuf::from(format!(".\\SavedModel\\{}", task_info.subtask_uuid));
+        folder
+    }
+
+    fn media_folder(task_info: &TaskInfo) -> PathBuf {
+        #[cfg(target_os = "linux")]
+        let folder = PathBuf::from(format!("./SavedFile/{}", task_info.subtask_uuid));
+        #[cfg(target_os = "windows")]
+        let folder = PathBuf::from(format!(".\\SavedFile\\{}", task_info.subtask_uuid));
+        folder
+    }
+
+    fn result_folder(task_info: &TaskInfo) -> PathBuf {
+        #[cfg(target_os = "linux")]
+        let folder = PathBuf::from(format!("./Result/{}", task_info.subtask_uuid));
+        #[cfg(target_os = "windows")]
+        let folder = PathBuf::from(format!(".\\Result\\{}", task_info.subtask_uuid));
+        folder
+    }
+
+    //Releases the model pinned for the subtask and removes its folders once it is no longer referenced.
+    async fn release_task(task_info: &TaskInfo) {
+        if !task_info.model_hash.is_empty() && task_info.model_path.is_some() {
+            ModelCache::unpin(&task_info.model_hash).await;
+        }
+        for folder in [Self::model_folder(task_info), Self::media_folder(task_info), Self::result_folder(task_info)] {
+            if folder.exists() && let Err(err) = fs::remove_dir_all(&folder).await {
+                logging_error!(IOEntry::DeleteDirectoryError(folder.display(), err));
+            }
+        }
+    }
+
     async fn receive_task_info(agent: &Arc<RwLock<Agent>>) -> Result<TaskInfo, LogEntry> {
         if let Some(data_channel_receiver) = agent.write().await.data_channel_receiver.as_mut() {
             clear_unbounded_channel(&mut data_channel_receiver.task_info_packet).await;
@@ -265,7 +393,7 @@ impl Agent {
         let config = Config::now().await;
         let timer = Instant::now();
         let timeout_duration = Duration::from_secs(config.data_channel_timeout);
-        let task_info = loop {
+        let mut task_info = loop {
             if agent.read().await.state == AgentState::Terminate {
                 Err(information_entry!(SystemEntry::Cancel))?;
             }
@@ -286,19 +414,38 @@ impl Agent {
                 Err(warning_entry!(NetworkEntry::DataChannelNotReady))?
             }
         };
-        if let Some(data_channel_sender) = agent.write().await.data_channel_sender.as_mut() {
-            data_channel_sender.send(TaskInfoAckPacket::new()).await;
-        } else {
-            Err(warning_entry!(NetworkEntry::DataChannelNotReady))?;
+        //The model is only reported as cached when the pin that later serves its path succeeded.
+        task_info.model_path = ModelCache::pin(&task_info.model_hash).await;
+        let mut cached_models = ModelCache::hashes().await;
+        cached_models.retain(|hash| *hash != task_info.model_hash);
+        if task_info.model_path.is_some() {
+            cached_models.push(task_info.model_hash.clone());
+        }
+        let ack = match serde_json::to_vec(&cached_models) {
+            Ok(cached_models) => match agent.write().await.data_channel_sender.as_mut() {
+                Some(data_channel_sender) => {
+                    data_channel_sender.send(TaskInfoAckPacket::new(cached_models)).await;
+                    Ok(())
+                }
+                None => Err(warning_entry!(NetworkEntry::DataChannelNotReady)),
+            },
+            Err(err) => Err(error_entry!(IOEntry::SerdeSerializeError(err))),
+        };
+        if let Err(entry) = ack {
+            Self::release_task(&task_info).await;
+            Err(entry)?;
         }
         Ok(task_info)
     }
 
-    async fn receive_file(agent: &Arc<RwLock<Agent>>, save_folder: &PathBuf) -> Result<(), LogEntry> {
+    async fn receive_file(agent: &Arc<RwLock<Agent>>, save_folder: &PathBuf) -> Result<PathBuf, LogEntry> {
+        let chunk_size = agent.read().await.negotiation.chunk_size;
         let file_header = Self::receive_file_header(agent).await?;
-        let file_body = Self::receive_file_body(agent, &file_header).await?;
-        Self::create_file(file_header, file_body, save_folder).await?;
-        Ok(())
+        let file_path = save_folder.join(&file_header.file_name);
+        let mut file_writer = FileWriter::create(save_folder, file_header, chunk_size).await?;
+        Self::receive_file_body(agent, &mut file_writer).await?;
+        file_writer.finish().await?;
+        Ok(file_path)
     }
 
     async fn receive_file_header(agent: &Arc<RwLock<Agent>>) -> Result<FileHeader, LogEntry> {
@@ -337,114 +484,90 @@ impl Agent {
         Ok(file_header)
     }
 
-    async fn receive_file_body(agent: &Arc<RwLock<Agent>>, file_header: &FileHeader) -> Result<Vec<Vec<u8>>, LogEntry> {
+    async fn receive_file_body(agent: &Arc<RwLock<Agent>>, file_writer: &mut FileWriter) -> Result<(), LogEntry> {
         if let Some(data_channel_receiver) = agent.write().await.data_channel_receiver.as_mut() {
             clear_unbounded_channel(&mut data_channel_receiver.file_body_packet).await;
         }
         let config = Config::now().await;
-        let mut file_block: HashMap<usize, Vec<u8>> = HashMap::new();
-        let mut missing_blocks = Vec::new();
         let mut timer = Instant::now();
         let timeout_duration = Duration::from_secs(config.data_channel_timeout);
-        loop {
+        while !file_writer.is_complete() {
             if agent.read().await.state == AgentState::Terminate {
                 Err(information_entry!(SystemEntry::Cancel))?;
             }
             if timer.elapsed() > timeout_duration {
                 Err(information_entry!(NetworkEntry::DataChannelTimeout))?;
             }
-            if let Some(data_channel_receiver) = agent.write().await.data_channel_receiver.as_mut() {
+            let missing_chunks = if let Some(data_channel_receiver) = agent.write().await.data_channel_receiver.as_mut() {
                 select! {
                     biased;
                     packet = data_channel_receiver.file_body_packet.recv() => {
                         let packet = packet
                             .ok_or(information_entry!(NetworkEntry::ChannelClosed))?;
-                        let (sequence_bytes, file_body) = packet.data.split_at(size_of::<usize>());
-                        let sequence_bytes = sequence_bytes.try_into()
-                            .map_err(|_| error_entry!(MiscEntry::InvalidPacket))?;
-                        let sequence_number = usize::from_be_bytes(sequence_bytes);
-                        file_block.insert(sequence_number, Vec::from(file_body));
+                        if !file_writer.write_chunk(packet.as_data_byte()).await? {
+                            logging_warning!(MiscEntry::ChunkChecksumMismatch);
+                        }
                         timer = Instant::now();
                         continue;
                     }
                     packet = data_channel_receiver.file_transfer_end_packet.recv() => {
                         clear_unbounded_channel(&mut data_channel_receiver.file_transfer_end_packet).await;
-                        let _ = packet
+                        let packet = packet
                             .ok_or(information_entry!(NetworkEntry::ChannelClosed))?;
-                        for sequence_number in 0..file_header.packet_count {
-                            if !file_block.contains_key(&sequence_number) {
-                                missing_blocks.push(sequence_number);
-                            }
-                        }
                         timer = Instant::now();
+                        file_writer.end_window(packet.as_data_byte())
                     }
                     _ = sleep(Duration::from_millis(config.internal_timestamp)) => continue
                 }
             } else {
-                Err(warning_entry!(NetworkEntry::DataChannelNotReady))?;
-            }
-            if let Some(data_channel_sender) = agent.write().await.data_channel_sender.as_mut() {
-                if missing_blocks.len() != 0_usize {
-                    let missing_blocks = mem::take(&mut missing_blocks);
-                    let result = FileTransferResult::new(Some(missing_blocks));
-                    let result_data = serde_json::to_vec(&result)
-                        .map_err(|err| error_entry!(IOEntry::SerdeSerializeError(err)))?;
-                    data_channel_sender.send(FileTransferResultPacket::new(result_data)).await;
-                } else {
-                    let result = FileTransferResult::new(None);
-                    let result_data = serde_json::to_vec(&result)
-                        .map_err(|err| error_entry!(IOEntry::SerdeSerializeError(err)))?;
-                    data_channel_sender.send(FileTransferResultPacket::new(result_data)).await;
-                    let mut sorted_blocks: Vec<Vec<u8>> = Vec::with_capacity(file_header.packet_count);
-                    for index in 0..file_header.packet_count {
-                        if let Some(block) = file_block.remove(&index) {
-                            sorted_blocks.push(block);
-                        } else {
-                            Err(error_entry!(MiscEntry::MissingFileBlockError))?
-                        }
-                    }
-                    return Ok(sorted_blocks);
-                };
-            } else {
-                Err(warning_entry!(NetworkEntry::DataChannelNotReady))?;
-            }
-        }
-    }
-
-    async fn create_file(file_header: FileHeader, file_body: Vec<Vec<u8>>, saved_folder: &PathBuf) -> Result<(), LogEntry> {
-        let saved_path = saved_folder.join(file_header.file_name);
-        let mut file = File::create(&saved_path).await
-            .map_err(|err| error_entry!(IOEntry::CreateFileError(saved_path.display(), err)))?;
-        for chunk in file_body {
-            file.write_all(&chunk).await
-                .map_err(|err| error_entry!(IOEntry::WriteFileError(saved_path.display(), err)))?;
+                Err(warning_entry!(NetworkEntry::DataChannelNotReady))?
+            };
+            let mut agent = agent.write().await;
+            let data_channel_sender = agent.data_channel_sender.as_mut()
+                .ok_or(warning_entry!(NetworkEntry::DataChannelNotReady))?;
+            let result = FileTransferResult::new(missing_chunks);
+            let result_data = serde_json::to_vec(&result)
+                .map_err(|err| error_entry!(IOEntry::SerdeSerializeError(err)))?;
+            data_channel_sender.send(FileTransferResultPacket::new(result_data)).await;
         }
         Ok(())
     }
 
-    async fn waiting_inference(agent: &Arc<RwLock<Agent>>, task_info: &TaskInfo) -> Result<(), LogEntry> {
+    async fn waiting_inference(agent: &Arc<RwLock<Agent>>) -> Result<(TaskInfo, InferenceResult), LogEntry> {
         if let Some(data_channel_receiver) = agent.write().await.data_channel_receiver.as_mut() {
             clear_unbounded_channel(&mut data_channel_receiver.still_process_packet).await;
         }
         let config = Config::now().await;
-        let task_info = task_info.clone();
         let mut timer = Instant::now();
         let timeout_duration = Duration::from_secs(config.control_channel_timeout);
-        let join_handle = tokio::spawn(Self::inference(task_info));
         loop {
             if agent.read().await.state == AgentState::Terminate {
+                agent.write().await.abort_inference().await;
                 Err(information_entry!(SystemEntry::Cancel))?;
             }
             if timer.elapsed() > timeout_duration {
                 Err(information_entry!(NetworkEntry::DataChannelTimeout))?;
             }
-            if join_handle.is_finished() {
-                break join_handle.await
-                    .map_err(|err| error_entry!(SystemEntry::TaskPanickedError(err)))?;
-            }
             let mut agent = agent.write().await;
+            agent.reap_inference().await;
+            agent.start_inference().await;
+            if let Some(completed) = agent.completed_inference.pop_front() {
+                return Ok(completed);
+            }
+            if agent.running_inference.is_empty() && agent.pending_inference.is_empty() {
+                Err(error_entry!(MiscEntry::WrongDeliverOrder))?;
+            }
             if let Some(data_channel_receiver) = agent.data_channel_receiver.as_mut() {
                 select! {
+                    biased;
+                    packet = data_channel_receiver.cancel_task_packet.recv() => {
+                        let packet = packet.ok_or(information_entry!(NetworkEntry::ChannelClosed))?;
+                        match serde_json::from_slice::<Uuid>(packet.as_data_byte()) {
+                            Ok(task_uuid) => agent.cancel_inference(task_uuid),
+                            Err(err) => logging_error!(IOEntry::SerdeDeserializeError(err)),
+                        }
+                        continue;
+                    }
                     packet = data_channel_receiver.still_process_packet.recv() => {
                         let _ = packet.ok_or(information_entry!(NetworkEntry::ChannelClosed))?;
                         clear_unbounded_channel(&mut data_channel_receiver.still_process_packet).await;
@@ -463,27 +586,121 @@ impl Agent {
         }
     }
 
-    async fn inference(task_info: TaskInfo) -> Result<(), LogEntry> {
-        let inference_argument = task_info.inference_argument;
-        let model_path = PathBuf::from(format!("./SavedModel/{}", task_info.model_file_name));
-        let media_path = PathBuf::from(format!("./SavedFile/{}", task_info.media_file_name));
-        match (inference_argument.model_type, media_path.extension().and_then(OsStr::to_str)) {
-            (ModelType::Ultralytics, Some("png") | Some("jpg") | Some("jpeg")) =>
-                InferenceManager::ultralytics_inference_image(inference_argument, model_path, media_path).await,
-            (ModelType::Ultralytics, Some("mp4")) =>
-                InferenceManager::ultralytics_inference_video(inference_argument, model_path, media_path).await,
-            (ModelType::YOLOv4, Some("png") | Some("jpg") | Some("jpeg")) =>
-                InferenceManager::yolov4_inference_picture(inference_argument, model_path, media_path).await,
-            (ModelType::YOLOv4, Some("mp4")) =>
-                InferenceManager::yolov4_inference_video(inference_argument, model_path, media_path).await,
-            (ModelType::YOLOv7, Some("png") | Some("jpg") | Some("jpeg")) =>
-                InferenceManager::yolov7_inference_picture(inference_argument, model_path, media_path).await,
-            (ModelType::YOLOv7, Some("mp4")) =>
-                InferenceManager::yolov7_inference_video(inference_argument, model_path, media_path).await,
-            _ => Err(error_entry!(TaskEntry::UnSupportFileType(task_info.uuid))),
+    async fn start_inference(&mut self) {
+        while let Some(task_info) = self.pending_inference.front() {
+            let Some(device) = self.select_device(task_info).await else {
+                break;
+            };
+            if let Some(mut task_info) = self.pending_inference.pop_front() {
+                task_info.device = device;
+                let join_handle = tokio::spawn(InferenceManager::on_device(device, Self::inference(task_info.clone())));
+                self.running_inference.push((task_info, join_handle));
+            }
+        }
+    }
+
+    //The device chosen by management is kept, a busy device only takes another subtask while its free VRAM fits it.
+    async fn select_device(&self, task_info: &TaskInfo) -> Option<Option<u32>> {
+        let information = Monitor::get_system_info().await;
+        let performance = Monitor::get_performance().await;
+        let devices = match task_info.device {
+            Some(device) if self.devices.contains(&Some(device)) => vec![Some(device)],
+            _ => self.devices.clone(),
+        };
+        devices.into_iter()
+            .map(|device| (device, self.running_inference.iter().filter(|(task_info, _)| task_info.device == device).count()))
+            .filter(|(_, running)| *running < self.concurrency)
+            .filter(|(device, running)| {
+                let free_vram = match device {
+                    Some(index) => information.gpus.iter()
+                        .find(|gpu_device| gpu_device.index == *index)
+                        .map(|gpu_device| gpu_device.vram - performance.device_vram(*index).unwrap_or_default())
+                        .unwrap_or_default(),
+                    None => information.vram - performance.vram,
+                };
+                *running == 0 || free_vram >= task_info.estimated_vram
+            })
+            .min_by_key(|(_, running)| *running)
+            .map(|(device, _)| device)
+    }
+
+    async fn reap_inference(&mut self) {
+        let mut index = 0_usize;
+        while index < self.running_inference.len() {
+            if !self.running_inference[index].1.is_finished() {
+                index += 1;
+                continue;
+            }
+            let (task_info, join_handle) = self.running_inference.remove(index);
+            let result = match join_handle.await {
+                Ok(result) => result,
+                Err(err) if err.is_cancelled() => Err(information_entry!(TaskEntry::TaskCancelled(task_info.uuid))),
+                Err(err) => Err(error_entry!(SystemEntry::TaskPanickedError(err))),
+            };
+            self.completed_inference.push_back((task_info, result));
+        }
+    }
+
+    fn cancel_inference(&mut self, task_uuid: Uuid) {
+        for (task_info, join_handle) in &self.running_inference {
+            if task_info.uuid == task_uuid {
+                join_handle.abort();
+            }
+        }
+        let (cancelled, pending): (VecDeque<_>, VecDeque<_>) = mem::take(&mut self.pending_inference)
+            .into_iter()
+            .partition(|task_info| task_info.uuid == task_uuid);
+        self.pending_inference = pending;
+        for task_info in cancelled {
+            let result = Err(information_entry!(TaskEntry::TaskCancelled(task_uuid)));
+            self.completed_inference.push_back((task_info, result));
+        }
+    }
+
+    async fn abort_inference(&mut self) {
+        for (task_info, join_handle) in self.running_inference.drain(..) {
+            join_handle.abort();
+            Self::release_task(&task_info).await;
+        }
+        for task_info in self.pending_inference.drain(..) {
+            Self::release_task(&task_info).await;
+        }
+        for (task_info, _) in self.completed_inference.drain(..) {
+            Self::release_task(&task_info).await;
         }
     }
 
+    async fn inference(task_info: TaskInfo) -> InferenceResult {
+        let model_path = task_info.model_path.clone()
+            .unwrap_or_else(|| Self::model_folder(&task_info).join(&task_info.model_file_name));
+        let media_path = Self::media_folder(&task_info).join(&task_info.media_file_name);
+        let result_folder = Self::result_folder(&task_info);
+        let inference_argument = task_info.inference_argument;
+        let backend = InferenceManager::backend(&inference_argument.model_type).await
+            .ok_or(error_entry!(TaskEntry::BackendUnavailable(format!("{:?}", inference_argument.model_type))))?;
+        let capability = backend.capability();
+        backend.prepare_model(&model_path).await?;
+        let inference = match media_path.extension().and_then(OsStr::to_str) {
+            Some("png") | Some("jpg") | Some("jpeg") if capability.image =>
+                backend.infer_image(&inference_argument, &model_path, &media_path, &result_folder),
+            Some("mp4") if capability.video =>
+                backend.infer_video(&inference_argument, &model_path, &media_path, &result_folder),
+            _ => Err(error_entry!(TaskEntry::UnSupportFileType(task_info.uuid)))?,
+        };
+        //Dropping the inference future kills the child process tree of script based backends.
+        let time_limit = inference_argument.timeout.unwrap_or(Config::now().await.inference_timeout);
+        let (result, resource_usage) = ResourceSampler::measure(async {
+            if time_limit > 0 {
+                timeout(Duration::from_secs(time_limit), inference).await
+                    .unwrap_or_else(|_| Err(error_entry!(TaskEntry::InferenceTimeout(time_limit))))
+            } else {
+                inference.await
+            }
+        }).await;
+        backend.cleanup(&media_path).await?;
+        result.map(|_| resource_usage)
+    }
+
     async fn notice_complete(agent: &Arc<RwLock<Agent>>, task_result: &TaskResult) -> Result<(), LogEntry> {
         let config = Config::now().await;
         let task_result_data = serde_json::to_vec(task_result)
@@ -525,38 +742,26 @@ impl Agent {
     }
 
     async fn transfer_result(agent: &Arc<RwLock<Agent>>, task_info: &TaskInfo) -> Result<(), LogEntry> {
-        let file_name = task_info.media_file_name.clone();
-        let file_path = PathBuf::from(format!("./Result/{}", file_name));
-        let file_body = Self::read_file(agent, &file_path).await?;
-        Self::transfer_file_header(agent, &file_name, file_body.len()).await?;
-        Self::transfer_file_body(agent, file_body).await?;
+        let result_folder = Self::result_folder(task_info);
+        let media_file_name = task_info.media_file_name.clone();
+        let detection_file_name = format!("{}.json", media_file_name);
+        Self::transfer_file(agent, &result_folder, &media_file_name).await?;
+        Self::transfer_file(agent, &result_folder, &detection_file_name).await?;
         Ok(())
     }
 
-    async fn read_file(agent: &Arc<RwLock<Agent>>, file_path: &PathBuf) -> Result<Vec<Vec<u8>>, LogEntry> {
-        let mut sequence_number = 0_usize;
-        let mut buffer = vec![0; 1_048_576];
-        let mut packets = Vec::new();
-        let mut file = File::open(file_path.clone()).await
-            .map_err(|err| error_entry!(IOEntry::ReadFileError(file_path.display(), err)))?;
-        while agent.read().await.state != AgentState::Terminate {
-            let bytes_read = file.read(&mut buffer).await
-                .map_err(|err| error_entry!(IOEntry::ReadFileError(file_path.display(), err)))?;
-            if bytes_read == 0 {
-                return Ok(packets);
-            }
-            let mut data = sequence_number.to_be_bytes().to_vec();
-            data.extend_from_slice(&buffer[..bytes_read]);
-            packets.push(data);
-            sequence_number += 1;
-        }
-        Err(information_entry!(SystemEntry::Cancel))?
+    async fn transfer_file(agent: &Arc<RwLock<Agent>>, result_folder: &Path, file_name: &String) -> Result<(), LogEntry> {
+        let file_path = result_folder.join(file_name);
+        let chunk_size = agent.read().await.negotiation.chunk_size;
+        let mut file_reader = FileReader::open(&file_path, chunk_size).await?;
+        let file_header = file_reader.file_header(file_name.clone(), TRANSFER_WINDOW_SIZE);
+        Self::transfer_file_header(agent, &file_header).await?;
+        Self::transfer_file_body(agent, &mut file_reader, &file_header).await
     }
 
-    async fn transfer_file_header(agent: &Arc<RwLock<Agent>>, file_name: &String, packet_count: usize) -> Result<(), LogEntry> {
+    async fn transfer_file_header(agent: &Arc<RwLock<Agent>>, file_header: &FileHeader) -> Result<(), LogEntry> {
         let config = Config::now().await;
-        let file_header = FileHeader::new(file_name.clone(), packet_count);
-        let file_header_data = serde_json::to_vec(&file_header)
+        let file_header_data = serde_json::to_vec(file_header)
             .map_err(|err| error_entry!(IOEntry::SerdeSerializeError(err)))?;
         let timer = Instant::now();
         let mut polling_times = 0_u32;
@@ -593,10 +798,20 @@ impl Agent {
         Err(information_entry!(SystemEntry::Cancel))
     }
 
-    async fn transfer_file_body(agent: &Arc<RwLock<Agent>>, file_body: Vec<Vec<u8>>) -> Result<(), LogEntry> {
+    async fn transfer_file_body(agent: &Arc<RwLock<Agent>>, file_reader: &mut FileReader, file_header: &FileHeader) -> Result<(), LogEntry> {
+        let mut window_start = 0_usize;
+        while window_start < file_header.packet_count {
+            let window_end = file_header.window_end(window_start);
+            file_reader.read_window(window_start, window_end).await?;
+            Self::transfer_window(agent, file_reader, (window_start..window_end).collect()).await?;
+            window_start = window_end;
+        }
+        Ok(())
+    }
+
+    async fn transfer_window(agent: &Arc<RwLock<Agent>>, file_reader: &FileReader, mut require_send: Vec<usize>) -> Result<(), LogEntry> {
         let config = Config::now().await;
         let mut timer = Instant::now();
-        let mut require_send: Vec<usize> = (0..file_body.len()).collect();
         let timeout_duration = Duration::from_secs(config.file_transfer_timeout);
         while agent.read().await.state != AgentState::Terminate {
             if timer.elapsed() > timeout_duration {
@@ -604,7 +819,7 @@ impl Agent {
                 Err(information_entry!(NetworkEntry::DataChannelTimeout))?;
             }
             for chunk in &require_send {
-                let data = file_body.get(*chunk)
+                let data = file_reader.chunk(*chunk)
                     .ok_or(error_entry!(MiscEntry::MissingFileBlockError))?;
                 let mut agent = agent.write().await;
                 let data_channel_sender = agent.data_channel_sender.as_mut()
@@ -615,7 +830,7 @@ impl Agent {
                 let mut agent = agent.write().await;
                 let data_channel_sender = agent.data_channel_sender.as_mut()
                     .ok_or(warning_entry!(NetworkEntry::DataChannelNotReady))?;
-                data_channel_sender.send(FileTransferEndPacket::new()).await;
+                data_channel_sender.send(FileTransferEndPacket::new(file_reader.trailer())).await;
                 require_send = Vec::new();
                 timer = Instant::now();
             }
@@ -676,6 +891,7 @@ impl Agent {
     async fn create_data_channel(agent: &Arc<RwLock<Agent>>) -> Result<(), LogEntry> {
         {
             let mut agent = agent.write().await;
+            agent.abort_inference().await;
             clear_unbounded_channel(&mut agent.control_channel_receiver.data_channel_port_packet).await;
         }
         let config = Config::now().await;
@@ -716,7 +932,8 @@ impl Agent {
                 let address = format!("{}:{}", config.management_address, port);
                 let tcp_stream = TcpStream::connect(&address).await
                     .map_err(|err| error_entry!(NetworkEntry::EstablishConnectionError(err)))?;
-                let socket_stream = SocketStream::new(tcp_stream);
+                let timeout_duration = Duration::from_secs(config.data_channel_timeout);
+                let socket_stream = config.tls.connect(tcp_stream, &config.management_address, timeout_duration).await?;
                 let (data_channel_sender, data_channel_receiver) = DataChannel::new(socket_stream);
                 let mut agent = agent.write().await;
                 agent.data_channel_sender = Some(data_channel_sender);
@@ -13,7 +13,7 @@ pub struct FileManager;
 impl FileManager {
     pub async fn initialize() {
         logging_information!(SystemEntry::Initializing);
-        let folders = ["SavedModel", "SavedFile", "Result", "Script", "Script/ultralytics"];
+        let folders = ["SavedModel", "SavedFile", "Result", "Script", "Script/ultralytics", "Script/yolo"];
         for &folder_name in &folders {
             let path = PathBuf::from(folder_name);
             if let Err(err) = fs::create_dir(folder_name).await {
@@ -0,0 +1,196 @@
+use crate::management::inference_backend::{BackendFuture, InferenceBackend};
+use crate::management::inference_manager::InferenceManager;
+use crate::management::utils::backend_capability::BackendCapability;
+use crate::management::utils::inference_argument::{InferenceArgument, ModelType};
+use crate::utils::logging::*;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CustomBackendConfig {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub arguments: Vec<String>,
+    #[serde(default = "CustomBackendConfig::default_result_file")]
+    pub result_file: String,
+    #[serde(default = "CustomBackendConfig::default_detection_file")]
+    pub detection_file: String,
+    #[serde(default)]
+    pub timeout: u64, //seconds, zero for no limit
+    #[serde(default = "CustomBackendConfig::default_support")]
+    pub image: bool,
+    #[serde(default = "CustomBackendConfig::default_support")]
+    pub video: bool,
+}
+
+impl CustomBackendConfig {
+    fn default_result_file() -> String {
+        "{output}/{media_name}".to_string()
+    }
+
+    fn default_detection_file() -> String {
+        "{output}/{media_name}.json".to_string()
+    }
+
+    fn default_support() -> bool {
+        true
+    }
+
+    pub fn validate(custom_backends: &[CustomBackendConfig]) -> bool {
+        custom_backends.iter().enumerate().all(|(index, custom_backend)| {
+            !custom_backend.name.is_empty()
+                && !custom_backend.command.is_empty()
+                && (custom_backend.image || custom_backend.video)
+                && custom_backends[..index].iter().all(|other| other.name != custom_backend.name)
+        })
+    }
+}
+
+pub struct CustomBackend {
+    config: CustomBackendConfig,
+}
+
+impl CustomBackend {
+    pub fn new(config: CustomBackendConfig) -> Self {
+        Self {
+            config,
+        }
+    }
+
+    fn render(template: &str, inference_argument: &InferenceArgument, model_path: &Path, media_path: &Path, save_folder: &Path) -> String {
+        let media_name = media_path.file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        template
+            .replace("{model}", &model_path.to_string_lossy())
+            .replace("{media_name}", &media_name)
+            .replace("{media}", &media_path.to_string_lossy())
+            .replace("{output}", &save_folder.to_string_lossy())
+            .replace("{mode}", &inference_argument.detect_mode.to_string())
+            .replace("{imgsz}", &inference_argument.imgsz.to_string())
+            .replace("{conf}", &inference_argument.conf.to_string())
+            .replace("{batch}", &inference_argument.batch.to_string())
+    }
+
+    async fn inference(&self, inference_argument: &InferenceArgument, model_path: &Path, media_path: &Path, save_folder: &Path) -> Result<(), LogEntry> {
+        let arguments: Vec<OsString> = self.config.arguments.iter()
+            .map(|argument| Self::render(argument, inference_argument, model_path, media_path, save_folder).into())
+            .collect();
+        let time_limit = (self.config.timeout > 0).then(|| Duration::from_secs(self.config.timeout));
+        InferenceManager::run_command(&self.config.command, arguments, time_limit).await?;
+        let media_name = media_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let expected_files = [
+            (&self.config.result_file, save_folder.join(&media_name)),
+            (&self.config.detection_file, save_folder.join(format!("{}.json", media_name))),
+        ];
+        for (template, destination) in expected_files {
+            let output_path = PathBuf::from(Self::render(template, inference_argument, model_path, media_path, save_folder));
+            if !output_path.exists() {
+                let err = format!("Backend {} did not produce {}", self.config.name, output_path.display());
+                Err(error_entry!(SystemEntry::ChildProcessError(err)))?;
+            }
+            if output_path != destination {
+                fs::rename(&output_path, &destination).await
+                    .map_err(|err| error_entry!(IOEntry::MoveFileError(output_path.display(), destination.display(), err)))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn command_exists(command: &str) -> bool {
+        let command_path = Path::new(command);
+        if command_path.components().count() > 1 {
+            return command_path.exists();
+        }
+        env::var_os("PATH")
+            .map(|paths| env::split_paths(&paths).any(|folder| {
+                folder.join(command).exists()
+                    || (cfg!(target_os = "windows") && folder.join(format!("{}.exe", command)).exists())
+            }))
+            .unwrap_or(false)
+    }
+}
+
+impl InferenceBackend for CustomBackend {
+    fn capability(&self) -> BackendCapability {
+        let model_type = ModelType::Custom(self.config.name.clone());
+        BackendCapability::new(&self.config.name, model_type, self.config.image, self.config.video)
+    }
+
+    fn available(&self) -> bool {
+        Self::command_exists(&self.config.command)
+    }
+
+    fn infer_image<'a>(&'a self, inference_argument: &'a InferenceArgument,
+                       model_path: &'a Path, image_path: &'a Path, save_folder: &'a Path) -> BackendFuture<'a>
+    {
+        Box::pin(self.inference(inference_argument, model_path, image_path, save_folder))
+    }
+
+    fn infer_video<'a>(&'a self, inference_argument: &'a InferenceArgument,
+                       model_path: &'a Path, video_path: &'a Path, save_folder: &'a Path) -> BackendFuture<'a>
+    {
+        Box::pin(self.inference(inference_argument, model_path, video_path, save_folder))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::management::utils::inference_argument::DetectMode;
+
+    fn inference_argument() -> InferenceArgument {
+        InferenceArgument {
+            model_type: ModelType::Custom("detector".to_string()),
+            detect_mode: DetectMode::Track,
+            cache: false,
+            imgsz: 1280,
+            batch: 4,
+            conf: 0.5,
+            timeout: None,
+        }
+    }
+
+    #[test]
+    fn render_replaces_every_placeholder() {
+        let template = "--model={model} --source={media} --output={output} --mode={mode} --imgsz={imgsz} --conf={conf} --batch={batch}";
+        let rendered = CustomBackend::render(template, &inference_argument(), Path::new("models/best.pt"),
+                                             Path::new("media/clip.mp4"), Path::new("results"));
+        assert_eq!(rendered, "--model=models/best.pt --source=media/clip.mp4 --output=results --mode=track --imgsz=1280 --conf=0.5 --batch=4");
+    }
+
+    #[test]
+    fn render_media_name_is_not_replaced_as_media() {
+        let rendered = CustomBackend::render("{output}/{media_name}.json", &inference_argument(), Path::new("best.pt"),
+                                             Path::new("media/clip.mp4"), Path::new("results"));
+        assert_eq!(rendered, "results/clip.mp4.json");
+    }
+
+    #[test]
+    fn render_keeps_unknown_placeholders() {
+        let rendered = CustomBackend::render("{device} {model}", &inference_argument(), Path::new("best.pt"),
+                                             Path::new("clip.mp4"), Path::new("results"));
+        assert_eq!(rendered, "{device} best.pt");
+    }
+
+    #[test]
+    fn validate_rejects_duplicate_names() {
+        let config = CustomBackendConfig {
+            name: "detector".to_string(),
+            command: "detect".to_string(),
+            arguments: Vec::new(),
+            result_file: CustomBackendConfig::default_result_file(),
+            detection_file: CustomBackendConfig::default_detection_file(),
+            timeout: 0,
+            image: true,
+            video: false,
+        };
+        assert!(CustomBackendConfig::validate(std::slice::from_ref(&config)));
+        assert!(!CustomBackendConfig::validate(&[config.clone(), config]));
+    }
+}
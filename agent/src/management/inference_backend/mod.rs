@@ -0,0 +1,35 @@
+pub mod custom;
+pub mod onnx;
+pub mod onnx_detector;
+pub mod ultralytics;
+pub mod yolo;
+
+use crate::management::utils::backend_capability::BackendCapability;
+use crate::management::utils::inference_argument::InferenceArgument;
+use crate::utils::logging::*;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+
+pub type BackendFuture<'a> = Pin<Box<dyn Future<Output = Result<(), LogEntry>> + Send + 'a>>;
+
+pub trait InferenceBackend: Send + Sync {
+    fn capability(&self) -> BackendCapability;
+
+    fn available(&self) -> bool;
+
+    fn prepare_model<'a>(&'a self, _model_path: &'a Path) -> BackendFuture<'a> {
+        Box::pin(async { Ok(()) })
+    }
+
+    //Results are written to save_folder as {media_name} and {media_name}.json.
+    fn infer_image<'a>(&'a self, inference_argument: &'a InferenceArgument,
+                       model_path: &'a Path, image_path: &'a Path, save_folder: &'a Path) -> BackendFuture<'a>;
+
+    fn infer_video<'a>(&'a self, inference_argument: &'a InferenceArgument,
+                       model_path: &'a Path, video_path: &'a Path, save_folder: &'a Path) -> BackendFuture<'a>;
+
+    fn cleanup<'a>(&'a self, _media_path: &'a Path) -> BackendFuture<'a> {
+        Box::pin(async { Ok(()) })
+    }
+}
@@ -0,0 +1,81 @@
+use crate::management::inference_backend::onnx_detector::{Cancellation, OnnxDetector};
+use crate::management::inference_backend::{BackendFuture, InferenceBackend};
+use crate::management::utils::backend_capability::BackendCapability;
+use crate::management::utils::inference_argument::{InferenceArgument, ModelType};
+use crate::utils::logging::*;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::task;
+
+//The blocking thread outlives a timed out or aborted future, so cancel it when the future is dropped.
+struct CancelOnDrop(Arc<Cancellation>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+pub struct OnnxBackend {
+    available: bool,
+}
+
+impl OnnxBackend {
+    pub async fn new(onnxruntime_path: String) -> Self {
+        //Loading the runtime library panics when it cannot be found, so probe it on a blocking thread.
+        let result = task::spawn_blocking(move || {
+            let environment = if onnxruntime_path.is_empty() {
+                ort::init()
+            } else {
+                ort::init_from(onnxruntime_path)
+            };
+            environment.with_name("agent").commit()
+        }).await;
+        let available = match result {
+            Ok(Ok(_)) => true,
+            Ok(Err(err)) => {
+                logging_warning!(TaskEntry::OnnxRuntimeError(err.to_string()));
+                false
+            }
+            Err(_) => {
+                logging_warning!(TaskEntry::BackendUnavailable(format!("{:?}", ModelType::Onnx)));
+                false
+            }
+        };
+        Self {
+            available,
+        }
+    }
+}
+
+impl InferenceBackend for OnnxBackend {
+    fn capability(&self) -> BackendCapability {
+        BackendCapability::new("onnx", ModelType::Onnx, true, false)
+    }
+
+    fn available(&self) -> bool {
+        self.available
+    }
+
+    fn infer_image<'a>(&'a self, inference_argument: &'a InferenceArgument,
+                       model_path: &'a Path, image_path: &'a Path, save_folder: &'a Path) -> BackendFuture<'a>
+    {
+        let (imgsz, conf) = (inference_argument.imgsz, inference_argument.conf);
+        let (model_path, image_path, save_folder) = (model_path.to_path_buf(), image_path.to_path_buf(), save_folder.to_path_buf());
+        Box::pin(async move {
+            let cancellation = Arc::new(Cancellation::new()?);
+            let _guard = CancelOnDrop(cancellation.clone());
+            task::spawn_blocking(move || {
+                let mut detector = OnnxDetector::new(&model_path, imgsz)?;
+                detector.detect(&image_path, conf, &save_folder, &cancellation)
+            }).await
+                .map_err(|err| error_entry!(SystemEntry::TaskPanickedError(err)))?
+        })
+    }
+
+    fn infer_video<'a>(&'a self, _inference_argument: &'a InferenceArgument,
+                       _model_path: &'a Path, _video_path: &'a Path, _save_folder: &'a Path) -> BackendFuture<'a>
+    {
+        Box::pin(async { Err(error_entry!(TaskEntry::BackendUnavailable("onnx video".to_string()))) })
+    }
+}
@@ -0,0 +1,230 @@
+use crate::management::utils::detection_file::{Detection, DetectionFile, FrameDetection};
+use crate::utils::logging::*;
+use image::imageops::FilterType;
+use image::{Rgb, RgbImage};
+use imageproc::drawing::draw_hollow_rect_mut;
+use imageproc::rect::Rect;
+use ndarray::{s, Array4, ArrayViewD, Axis, Ix2};
+use ort::execution_providers::CPUExecutionProvider;
+use ort::session::{RunOptions, Session};
+use ort::value::Tensor;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const IOU_THRESHOLD: f32 = 0.45;
+
+struct Letterbox {
+    ratio: f32,
+    left: f32,
+    top: f32,
+}
+
+//Shared with the blocking detector, so a dropped inference future stops it at the next step or inside the session run.
+pub struct Cancellation {
+    cancelled: AtomicBool,
+    run_options: RunOptions,
+}
+
+impl Cancellation {
+    pub fn new() -> Result<Self, LogEntry> {
+        let run_options = RunOptions::new()
+            .map_err(|err| error_entry!(TaskEntry::OnnxRuntimeError(err.to_string())))?;
+        Ok(Self {
+            cancelled: AtomicBool::new(false),
+            run_options,
+        })
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        let _ = self.run_options.terminate();
+    }
+
+    fn check(&self) -> Result<(), LogEntry> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(error_entry!(TaskEntry::InferenceCancelled));
+        }
+        Ok(())
+    }
+}
+
+pub struct OnnxDetector {
+    session: Session,
+    names: BTreeMap<u32, String>,
+    imgsz: u32,
+}
+
+impl OnnxDetector {
+    pub fn new(model_path: &Path, imgsz: usize) -> Result<Self, LogEntry> {
+        let session = Session::builder()
+            .and_then(|builder| builder.with_execution_providers([CPUExecutionProvider::default().build()]))
+            .and_then(|builder| builder.commit_from_file(model_path))
+            .map_err(|err| error_entry!(TaskEntry::OnnxRuntimeError(err.to_string())))?;
+        let names = session.metadata().ok()
+            .and_then(|metadata| metadata.custom("names").ok().flatten())
+            .map(|names| Self::parse_names(&names))
+            .unwrap_or_default();
+        //Models exported with a static input shape ignore the requested image size.
+        let imgsz = session.inputs.first()
+            .and_then(|input| input.input_type.tensor_shape())
+            .and_then(|shape| shape.get(3).copied())
+            .filter(|size| *size > 0)
+            .map(|size| size as u32)
+            .unwrap_or(imgsz as u32);
+        Ok(Self {
+            session,
+            names,
+            imgsz,
+        })
+    }
+
+    pub fn detect(&mut self, image_path: &Path, conf: f32, save_folder: &Path, cancellation: &Cancellation) -> Result<(), LogEntry> {
+        cancellation.check()?;
+        let mut image = image::open(image_path)
+            .map_err(|err| error_entry!(TaskEntry::ImageProcessError(err.to_string())))?
+            .to_rgb8();
+        let (width, height) = image.dimensions();
+        let (input, letterbox) = self.preprocess(&image);
+        let tensor = Tensor::from_array(input)
+            .map_err(|err| error_entry!(TaskEntry::OnnxRuntimeError(err.to_string())))?;
+        let outputs = self.session.run_with_options(ort::inputs![tensor], &cancellation.run_options)
+            .map_err(|err| match cancellation.check() {
+                Err(entry) => entry,
+                Ok(_) => error_entry!(TaskEntry::OnnxRuntimeError(err.to_string())),
+            })?;
+        let output = outputs[0].try_extract_array::<f32>()
+            .map_err(|err| error_entry!(TaskEntry::OnnxRuntimeError(err.to_string())))?;
+        let detections = Self::postprocess(output, &letterbox, conf, &self.names, width, height)?;
+        for detection in &detections {
+            let [x1, y1, x2, y2] = detection.bbox;
+            let rect = Rect::at(x1 as i32, y1 as i32).of_size(((x2 - x1) as u32).max(1), ((y2 - y1) as u32).max(1));
+            draw_hollow_rect_mut(&mut image, rect, Rgb([0, 255, 0]));
+        }
+        let file_name = image_path.file_name()
+            .and_then(|file_name| file_name.to_str())
+            .unwrap_or_default();
+        let result_path = save_folder.join(file_name);
+        image.save(&result_path)
+            .map_err(|err| error_entry!(TaskEntry::ImageProcessError(err.to_string())))?;
+        let detection_file = DetectionFile {
+            width,
+            height,
+            frame_count: 1,
+            names: self.names.clone(),
+            frames: vec![FrameDetection { frame: 0, detections }],
+        };
+        let detection_data = serde_json::to_vec(&detection_file)
+            .map_err(|err| error_entry!(IOEntry::SerdeSerializeError(err)))?;
+        let detection_path = save_folder.join(format!("{}.json", file_name));
+        fs::write(&detection_path, detection_data)
+            .map_err(|err| error_entry!(IOEntry::WriteFileError(detection_path.display(), err)))?;
+        Ok(())
+    }
+
+    fn preprocess(&self, image: &RgbImage) -> (Array4<f32>, Letterbox) {
+        let (width, height) = image.dimensions();
+        let ratio = (self.imgsz as f32 / width as f32).min(self.imgsz as f32 / height as f32);
+        let resized_width = ((width as f32 * ratio).round() as u32).clamp(1, self.imgsz);
+        let resized_height = ((height as f32 * ratio).round() as u32).clamp(1, self.imgsz);
+        let resized = image::imageops::resize(image, resized_width, resized_height, FilterType::Triangle);
+        let left = (self.imgsz - resized_width) / 2;
+        let top = (self.imgsz - resized_height) / 2;
+        let size = self.imgsz as usize;
+        let mut input = Array4::<f32>::from_elem((1, 3, size, size), 114.0 / 255.0);
+        for (x, y, pixel) in resized.enumerate_pixels() {
+            for channel in 0..3 {
+                input[[0, channel, (y + top) as usize, (x + left) as usize]] = pixel[channel] as f32 / 255.0;
+            }
+        }
+        let letterbox = Letterbox {
+            ratio,
+            left: left as f32,
+            top: top as f32,
+        };
+        (input, letterbox)
+    }
+
+    fn postprocess(output: ArrayViewD<f32>, letterbox: &Letterbox, conf: f32, names: &BTreeMap<u32, String>,
+                   width: u32, height: u32) -> Result<Vec<Detection>, LogEntry>
+    {
+        let output = output.index_axis(Axis(0), 0).into_dimensionality::<Ix2>()
+            .map_err(|err| error_entry!(TaskEntry::OnnxRuntimeError(err.to_string())))?;
+        //Ultralytics exports [4 + classes, anchors], YOLOv5 style exports [anchors, 5 + classes].
+        let channels_first = output.shape()[0] < output.shape()[1];
+        let rows = if channels_first { output.t() } else { output };
+        let has_objectness = if names.is_empty() {
+            !channels_first
+        } else {
+            rows.shape()[1] == names.len() + 5
+        };
+        let mut candidates = Vec::new();
+        for row in rows.rows() {
+            let (objectness, scores) = if has_objectness {
+                (row[4], row.slice(s![5..]))
+            } else {
+                (1.0, row.slice(s![4..]))
+            };
+            let Some((class_id, score)) = scores.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)) else {
+                continue;
+            };
+            let confidence = objectness * score;
+            if confidence < conf {
+                continue;
+            }
+            let (center_x, center_y, box_width, box_height) = (row[0], row[1], row[2], row[3]);
+            let bbox = [
+                ((center_x - box_width / 2.0 - letterbox.left) / letterbox.ratio).clamp(0.0, width as f32),
+                ((center_y - box_height / 2.0 - letterbox.top) / letterbox.ratio).clamp(0.0, height as f32),
+                ((center_x + box_width / 2.0 - letterbox.left) / letterbox.ratio).clamp(0.0, width as f32),
+                ((center_y + box_height / 2.0 - letterbox.top) / letterbox.ratio).clamp(0.0, height as f32),
+            ];
+            candidates.push((class_id as u32, confidence, bbox));
+        }
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let mut kept: Vec<(u32, f32, [f32; 4])> = Vec::new();
+        for candidate in candidates {
+            if kept.iter().all(|kept| kept.0 != candidate.0 || Self::iou(&kept.2, &candidate.2) <= IOU_THRESHOLD) {
+                kept.push(candidate);
+            }
+        }
+        let detections = kept.into_iter()
+            .map(|(class_id, confidence, bbox)| Detection {
+                bbox: bbox.map(f64::from),
+                confidence: confidence as f64,
+                class_id,
+                class_name: names.get(&class_id).cloned().unwrap_or(class_id.to_string()),
+                track_id: None,
+            })
+            .collect();
+        Ok(detections)
+    }
+
+    fn iou(a: &[f32; 4], b: &[f32; 4]) -> f32 {
+        let width = (a[2].min(b[2]) - a[0].max(b[0])).max(0.0);
+        let height = (a[3].min(b[3]) - a[1].max(b[1])).max(0.0);
+        let intersection = width * height;
+        let union = (a[2] - a[0]) * (a[3] - a[1]) + (b[2] - b[0]) * (b[3] - b[1]) - intersection;
+        if union <= 0.0 { 0.0 } else { intersection / union }
+    }
+
+    //Ultralytics stores class names as a Python dict literal, e.g. "{0: 'person', 1: 'bicycle'}".
+    fn parse_names(names: &str) -> BTreeMap<u32, String> {
+        let mut parsed = BTreeMap::new();
+        let mut last_id = None;
+        for part in names.trim().trim_start_matches('{').trim_end_matches('}').split(", ") {
+            match part.split_once(": ").and_then(|(id, name)| Some((id.trim().parse::<u32>().ok()?, name))) {
+                Some((id, name)) => {
+                    parsed.insert(id, name.trim_matches(|c| c == '\'' || c == '"').to_string());
+                    last_id = Some(id);
+                }
+                None => if let Some(id) = last_id && let Some(name) = parsed.get_mut(&id) {
+                    name.push_str(", ");
+                    name.push_str(part.trim_end_matches(['\'', '"']));
+                }
+            }
+        }
+        parsed
+    }
+}
@@ -0,0 +1,54 @@
+use crate::management::inference_backend::{BackendFuture, InferenceBackend};
+use crate::management::inference_manager::InferenceManager;
+use crate::management::utils::backend_capability::BackendCapability;
+use crate::management::utils::inference_argument::{InferenceArgument, ModelType};
+use std::ffi::OsString;
+use std::path::Path;
+
+const PICTURE_SCRIPT: &str = "Script/ultralytics/picture_inference.py";
+const VIDEO_SCRIPT: &str = "Script/ultralytics/video_inference.py";
+
+pub struct UltralyticsBackend;
+
+impl InferenceBackend for UltralyticsBackend {
+    fn capability(&self) -> BackendCapability {
+        BackendCapability::new("ultralytics", ModelType::Ultralytics, true, true)
+    }
+
+    fn available(&self) -> bool {
+        Path::new(PICTURE_SCRIPT).exists() && Path::new(VIDEO_SCRIPT).exists()
+    }
+
+    fn infer_image<'a>(&'a self, inference_argument: &'a InferenceArgument,
+                       model_path: &'a Path, image_path: &'a Path, save_folder: &'a Path) -> BackendFuture<'a>
+    {
+        Box::pin(async move {
+            let arguments: Vec<OsString> = vec![
+                inference_argument.detect_mode.to_string().into(),
+                model_path.into(),
+                image_path.into(),
+                save_folder.into(),
+                inference_argument.imgsz.to_string().into(),
+                inference_argument.conf.to_string().into(),
+            ];
+            InferenceManager::run_script(PICTURE_SCRIPT, arguments).await
+        })
+    }
+
+    fn infer_video<'a>(&'a self, inference_argument: &'a InferenceArgument,
+                       model_path: &'a Path, video_path: &'a Path, save_folder: &'a Path) -> BackendFuture<'a>
+    {
+        Box::pin(async move {
+            let arguments: Vec<OsString> = vec![
+                inference_argument.detect_mode.to_string().into(),
+                model_path.into(),
+                video_path.into(),
+                save_folder.into(),
+                inference_argument.imgsz.to_string().into(),
+                inference_argument.conf.to_string().into(),
+                inference_argument.batch.to_string().into(),
+            ];
+            InferenceManager::run_script(VIDEO_SCRIPT, arguments).await
+        })
+    }
+}
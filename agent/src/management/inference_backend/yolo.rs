@@ -0,0 +1,74 @@
+use crate::management::inference_backend::{BackendFuture, InferenceBackend};
+use crate::management::inference_manager::InferenceManager;
+use crate::management::utils::backend_capability::BackendCapability;
+use crate::management::utils::inference_argument::{InferenceArgument, ModelType};
+use crate::utils::logging::*;
+use std::ffi::OsString;
+use std::path::Path;
+
+pub struct YoloBackend {
+    name: &'static str,
+    model_type: ModelType,
+    script: &'static str,
+    repository: &'static str,
+    repository_marker: &'static str,
+}
+
+impl YoloBackend {
+    pub fn yolov4() -> Self {
+        Self {
+            name: "yolov4",
+            model_type: ModelType::YOLOv4,
+            script: "Script/yolo/yolov4_inference.py",
+            repository: "Script/PyTorch_YOLOv4",
+            repository_marker: "Script/PyTorch_YOLOv4/models/models.py",
+        }
+    }
+
+    pub fn yolov7() -> Self {
+        Self {
+            name: "yolov7",
+            model_type: ModelType::YOLOv7,
+            script: "Script/yolo/yolov7_inference.py",
+            repository: "Script/yolov7",
+            repository_marker: "Script/yolov7/models/experimental.py",
+        }
+    }
+
+    async fn inference(&self, inference_argument: &InferenceArgument, model_path: &Path, media_path: &Path, save_folder: &Path) -> Result<(), LogEntry> {
+        let arguments: Vec<OsString> = vec![
+            inference_argument.detect_mode.to_string().into(),
+            model_path.into(),
+            media_path.into(),
+            save_folder.into(),
+            inference_argument.imgsz.to_string().into(),
+            inference_argument.conf.to_string().into(),
+            inference_argument.batch.to_string().into(),
+            "--repository".into(),
+            self.repository.into(),
+        ];
+        InferenceManager::run_script(self.script, arguments).await
+    }
+}
+
+impl InferenceBackend for YoloBackend {
+    fn capability(&self) -> BackendCapability {
+        BackendCapability::new(self.name, self.model_type.clone(), true, true)
+    }
+
+    fn available(&self) -> bool {
+        Path::new(self.script).exists() && Path::new(self.repository_marker).exists()
+    }
+
+    fn infer_image<'a>(&'a self, inference_argument: &'a InferenceArgument,
+                       model_path: &'a Path, image_path: &'a Path, save_folder: &'a Path) -> BackendFuture<'a>
+    {
+        Box::pin(self.inference(inference_argument, model_path, image_path, save_folder))
+    }
+
+    fn infer_video<'a>(&'a self, inference_argument: &'a InferenceArgument,
+                       model_path: &'a Path, video_path: &'a Path, save_folder: &'a Path) -> BackendFuture<'a>
+    {
+        Box::pin(self.inference(inference_argument, model_path, video_path, save_folder))
+    }
+}
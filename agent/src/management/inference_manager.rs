@@ -1,98 +1,174 @@
-use crate::management::utils::inference_argument::InferenceArgument;
+use crate::management::inference_backend::custom::CustomBackend;
+use crate::management::inference_backend::onnx::OnnxBackend;
+use crate::management::inference_backend::ultralytics::UltralyticsBackend;
+use crate::management::inference_backend::yolo::YoloBackend;
+use crate::management::inference_backend::InferenceBackend;
+use crate::management::monitor::Monitor;
+use crate::management::process_tree::ProcessTree;
+use crate::management::resource_sampler::ResourceSampler;
+use crate::management::utils::backend_capability::BackendCapability;
+use crate::management::utils::inference_argument::ModelType;
+use crate::utils::config::Config;
 use crate::utils::logging::*;
-use std::path::PathBuf;
+use lazy_static::lazy_static;
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::future::Future;
 use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::Command as AsyncCommand;
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tokio::time::timeout;
 
-pub struct InferenceManager;
+const STDERR_TAIL_LINES: usize = 20;
+
+tokio::task_local! {
+    static DEVICE: u32;
+}
+
+lazy_static! {
+    static ref INFERENCE_MANAGER: RwLock<InferenceManager> = RwLock::new(InferenceManager::new());
+}
+
+pub struct InferenceManager {
+    backends: Vec<Arc<dyn InferenceBackend>>,
+}
 
 impl InferenceManager {
-    pub async fn ultralytics_inference_image(inference_argument: InferenceArgument,
-                                             model_path: PathBuf, image_path: PathBuf) -> Result<(), LogEntry>
-    {
-        #[cfg(target_os = "windows")]
-        let python = "python";
-        #[cfg(target_os = "linux")]
-        let python = "python3";
-        let save_folder = PathBuf::from("./Result");
-        let mut process = AsyncCommand::new(python)
-            .arg("Script/ultralytics/picture_inference.py")
-            .arg(inference_argument.detect_mode.to_string())
-            .arg(model_path)
-            .arg(image_path)
-            .arg(save_folder)
-            .arg(inference_argument.imgsz.to_string())
-            .arg(inference_argument.conf.to_string())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|err| error_entry!(SystemEntry::ChildProcessError(err.to_string())))?;
-        let status = process.wait().await
-            .map_err(|err| error_entry!(SystemEntry::ChildProcessError(err.to_string())))?;
-        if !status.success() {
-            let err = format!("Process exit with code: {}", status.code().unwrap_or(-1));
-            Err(error_entry!(SystemEntry::ChildProcessError(err)))?
+    pub fn new() -> Self {
+        Self {
+            backends: Vec::new(),
         }
-        Ok(())
     }
 
-    pub async fn ultralytics_inference_video(inference_argument: InferenceArgument,
-                                             model_path: PathBuf, video_path: PathBuf) -> Result<(), LogEntry>
-    {
+    pub async fn instance() -> RwLockReadGuard<'static, Self> {
+        INFERENCE_MANAGER.read().await
+    }
+
+    pub async fn instance_mut() -> RwLockWriteGuard<'static, Self> {
+        INFERENCE_MANAGER.write().await
+    }
+
+    pub async fn initialize() {
+        let config = Config::now().await;
+        Self::register(Arc::new(UltralyticsBackend)).await;
+        Self::register(Arc::new(YoloBackend::yolov4())).await;
+        Self::register(Arc::new(YoloBackend::yolov7())).await;
+        Self::register(Arc::new(OnnxBackend::new(config.onnxruntime_path).await)).await;
+        for custom_backend in config.custom_backends {
+            Self::register(Arc::new(CustomBackend::new(custom_backend))).await;
+        }
+    }
+
+    pub async fn register(backend: Arc<dyn InferenceBackend>) {
+        let model_type = backend.capability().model_type;
+        let mut inference_manager = Self::instance_mut().await;
+        inference_manager.backends.retain(|registered| registered.capability().model_type != model_type);
+        inference_manager.backends.push(backend);
+    }
+
+    pub async fn backend(model_type: &ModelType) -> Option<Arc<dyn InferenceBackend>> {
+        Self::instance().await.backends.iter()
+            .find(|backend| backend.capability().model_type == *model_type && backend.available())
+            .cloned()
+    }
+
+    pub async fn capabilities() -> Vec<BackendCapability> {
+        Self::instance().await.backends.iter()
+            .filter(|backend| backend.available())
+            .map(|backend| backend.capability())
+            .collect()
+    }
+
+    //Pins every process started by the inference to a single GPU device.
+    pub async fn on_device<F: Future>(device: Option<u32>, inference: F) -> F::Output {
+        match device {
+            Some(device) => DEVICE.scope(device, inference).await,
+            None => inference.await,
+        }
+    }
+
+    pub async fn run_script(script: &str, arguments: Vec<OsString>) -> Result<(), LogEntry> {
         #[cfg(target_os = "windows")]
         let python = "python";
         #[cfg(target_os = "linux")]
         let python = "python3";
-        let save_folder = PathBuf::from("./Result");
-        let mut process = AsyncCommand::new(python)
-            .arg("Script/ultralytics/video_inference.py")
-            .arg(inference_argument.detect_mode.to_string())
-            .arg(model_path)
-            .arg(video_path)
-            .arg(save_folder)
-            .arg(inference_argument.imgsz.to_string())
-            .arg(inference_argument.conf.to_string())
-            .arg(inference_argument.batch.to_string())
+        let mut script_arguments: Vec<OsString> = vec![script.into()];
+        script_arguments.extend(arguments);
+        Self::run_command(python, script_arguments, None).await
+    }
+
+    pub async fn run_command(program: &str, arguments: Vec<OsString>, time_limit: Option<Duration>) -> Result<(), LogEntry> {
+        let mut command = AsyncCommand::new(program);
+        command.args(arguments)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|err| error_entry!(SystemEntry::ChildProcessError(err.to_string())))?;
-        let status = process.wait().await
+            .kill_on_drop(true);
+        //The child leads its own process group, so the whole tree can be signalled at once.
+        #[cfg(target_os = "linux")]
+        command.process_group(0);
+        if let Ok(device) = DEVICE.try_with(|device| *device) {
+            for (key, value) in Monitor::device_environment(device).await {
+                command.env(key, value);
+            }
+        }
+        let mut process = command.spawn()
             .map_err(|err| error_entry!(SystemEntry::ChildProcessError(err.to_string())))?;
+        let mut process_tree = ProcessTree::new(process.id());
+        if let Some(pid) = process.id() {
+            ResourceSampler::register(pid);
+        }
+        //Both pipes must be drained while waiting, otherwise a chatty process blocks once the pipe is full.
+        let stdout_reader = process.stdout.take().map(|stdout| tokio::spawn(Self::read_output(program.to_string(), stdout, false)));
+        let stderr_reader = process.stderr.take().map(|stderr| tokio::spawn(Self::read_output(program.to_string(), stderr, true)));
+        let mut timed_out = false;
+        let status = match time_limit {
+            Some(time_limit) => match timeout(time_limit, process.wait()).await {
+                Ok(status) => status,
+                Err(_) => {
+                    timed_out = true;
+                    //The leader is not reaped yet, so its group id still belongs to this tree.
+                    process_tree.kill();
+                    process.wait().await
+                }
+            },
+            None => process.wait().await,
+        }.map_err(|err| error_entry!(SystemEntry::ChildProcessError(err.to_string())))?;
+        process_tree.release();
+        if let Some(stdout_reader) = stdout_reader {
+            let _ = stdout_reader.await;
+        }
+        let stderr_tail = match stderr_reader {
+            Some(stderr_reader) => stderr_reader.await.unwrap_or_default(),
+            None => VecDeque::new(),
+        };
+        let stderr_tail = Vec::from(stderr_tail).join("\n");
+        if let Some(time_limit) = time_limit && timed_out {
+            return Err(error_entry!(TaskEntry::InferenceTimeout(time_limit.as_secs()), stderr_tail));
+        }
         if !status.success() {
             let err = format!("Process exit with code: {}", status.code().unwrap_or(-1));
-            Err(error_entry!(SystemEntry::ChildProcessError(err)))?
+            return Err(error_entry!(SystemEntry::ChildProcessError(err), stderr_tail));
         }
         Ok(())
     }
 
-    #[allow(unused_variables)]
-    pub async fn yolov4_inference_picture(inference_argument: InferenceArgument,
-                                          model_path: PathBuf, video_path: PathBuf) -> Result<(), LogEntry>
-    {
-        Ok(())
-    }
-
-    #[allow(unused_variables)]
-    pub async fn yolov4_inference_video(inference_argument: InferenceArgument,
-                                        model_path: PathBuf, video_path: PathBuf) -> Result<(), LogEntry>
-    {
-        Ok(())
-    }
-
-
-    #[allow(unused_variables)]
-    pub async fn yolov7_inference_picture(inference_argument: InferenceArgument,
-                                          model_path: PathBuf, video_path: PathBuf) -> Result<(), LogEntry>
-    {
-        Ok(())
-    }
-
-
-    #[allow(unused_variables)]
-    pub async fn yolov7_inference_video(inference_argument: InferenceArgument,
-                                        model_path: PathBuf, video_path: PathBuf) -> Result<(), LogEntry>
-    {
-        Ok(())
+    async fn read_output<R: AsyncRead + Unpin>(program: String, output: R, is_stderr: bool) -> VecDeque<String> {
+        let mut tail = VecDeque::new();
+        let mut lines = BufReader::new(output).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if is_stderr {
+                logging_warning!(format!("{}: {}", program, line));
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line);
+            } else {
+                logging_information!(format!("{}: {}", program, line));
+            }
+        }
+        tail
     }
 }
@@ -1,19 +1,22 @@
 use crate::connection::socket::management_socket::ManagementSocket;
 use crate::management::agent::Agent;
 use crate::management::file_manager::FileManager;
+use crate::management::inference_manager::InferenceManager;
+use crate::management::model_cache::ModelCache;
 use crate::management::monitor::Monitor;
 use crate::management::utils::agent_state::AgentState;
-use crate::utils::config::Config;
 use crate::utils::logging::*;
 use async_ctrlc::CtrlC;
 use lazy_static::lazy_static;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::select;
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use tokio::time::sleep;
 use common::utils::log_entry::system::SystemEntry;
 
+const MIN_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(60);
+
 lazy_static! {
     static ref MANAGEMENT: RwLock<Management> = RwLock::new(Management::new());
 }
@@ -41,6 +44,8 @@ impl Management {
 
     pub async fn run() {
         FileManager::initialize().await;
+        ModelCache::initialize().await;
+        InferenceManager::initialize().await;
         Monitor::run().await;
         tokio::spawn(async move {
             Self::hot_reload().await;
@@ -61,27 +66,34 @@ impl Management {
     }
 
     pub async fn hot_reload() {
-        let config = Config::now().await;
+        let mut backoff = MIN_RECONNECT_BACKOFF;
         while !Self::instance().await.terminate {
             let mut management = Self::instance_mut().await;
             if let Some(agent) = management.agent.clone() {
                 if agent.read().await.state == AgentState::Terminate {
                     management.agent = None;
                 }
-            } else {
-                select! {
-                    (socket_stream, management_ip) = ManagementSocket::get_connection() => {
-                        match Agent::new(socket_stream).await {
-                            Ok(agent) => {
-                                let agent = Arc::new(RwLock::new(agent));
-                                Agent::run(agent.clone()).await;
-                                management.agent = Some(agent);
-                                logging_information!(SystemEntry::ManagementConnect(management_ip));
-                            }
-                            Err(entry) => logging_entry!(entry)
-                        }
-                    }
-                    _ = sleep(Duration::from_secs(config.refresh_interval)) => continue
+                continue;
+            }
+            let connection = match ManagementSocket::get_connection().await {
+                Ok((socket_stream, management_ip)) => Agent::new(socket_stream).await
+                    .map(|agent| (agent, management_ip)),
+                Err(entry) => Err(entry),
+            };
+            match connection {
+                Ok((agent, management_ip)) => {
+                    let agent = Arc::new(RwLock::new(agent));
+                    Agent::run(agent.clone()).await;
+                    management.agent = Some(agent);
+                    backoff = MIN_RECONNECT_BACKOFF;
+                    logging_information!(SystemEntry::ManagementConnect(management_ip));
+                }
+                Err(entry) => {
+                    logging_entry!(entry);
+                    //Failed attempts are spaced out exponentially instead of retried immediately.
+                    drop(management);
+                    sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
                 }
             }
         }
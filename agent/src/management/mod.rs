@@ -1,7 +1,11 @@
 pub mod utils;
 pub mod agent;
 pub mod file_manager;
+pub mod inference_backend;
 pub mod inference_manager;
 pub mod management;
+pub mod model_cache;
+pub mod process_tree;
+pub mod resource_sampler;
 
 pub use common::management::*;
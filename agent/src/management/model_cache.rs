@@ -0,0 +1,155 @@
+use crate::utils::config::Config;
+use crate::utils::logging::*;
+use lazy_static::lazy_static;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::fs;
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+lazy_static! {
+    static ref MODEL_CACHE: RwLock<ModelCache> = RwLock::new(ModelCache::new());
+}
+
+struct CachedModel {
+    hash: String,
+    file_path: PathBuf,
+    size: u64,
+    pins: usize, //subtasks still referencing the model
+}
+
+pub struct ModelCache {
+    entries: VecDeque<CachedModel>, //least recently used first
+    used_size: u64,
+}
+
+impl ModelCache {
+    pub fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            used_size: 0,
+        }
+    }
+
+    pub async fn instance() -> RwLockReadGuard<'static, Self> {
+        MODEL_CACHE.read().await
+    }
+
+    pub async fn instance_mut() -> RwLockWriteGuard<'static, Self> {
+        MODEL_CACHE.write().await
+    }
+
+    pub async fn initialize() {
+        let folder = PathBuf::from("./ModelCache");
+        if !folder.exists() && let Err(err) = fs::create_dir(&folder).await {
+            logging_error!(IOEntry::CreateDirectoryError(folder.display(), err));
+            return;
+        }
+        let mut entries = match Self::scan(&folder).await {
+            Ok(entries) => entries,
+            Err(entry) => {
+                logging_entry!(entry);
+                return;
+            }
+        };
+        entries.sort_by_key(|(modified, _)| *modified);
+        let mut model_cache = Self::instance_mut().await;
+        for (_, cached_model) in entries {
+            model_cache.used_size += cached_model.size;
+            model_cache.entries.push_back(cached_model);
+        }
+        model_cache.evict(Config::now().await.model_cache_size).await;
+    }
+
+    async fn scan(folder: &Path) -> Result<Vec<(SystemTime, CachedModel)>, LogEntry> {
+        let mut entries = Vec::new();
+        let mut read_dir = fs::read_dir(folder).await
+            .map_err(|err| error_entry!(IOEntry::ReadDirectoryError(folder.display(), err)))?;
+        while let Ok(Some(dir_entry)) = read_dir.next_entry().await {
+            let file_path = dir_entry.path();
+            let hash = match file_path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(hash) => hash.to_string(),
+                None => continue,
+            };
+            let metadata = dir_entry.metadata().await
+                .map_err(|err| error_entry!(IOEntry::ReadFileError(file_path.display(), err)))?;
+            if !metadata.is_file() {
+                continue;
+            }
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            entries.push((modified, CachedModel { hash, file_path, size: metadata.len(), pins: 0 }));
+        }
+        Ok(entries)
+    }
+
+    pub async fn hashes() -> Vec<String> {
+        Self::instance().await.entries.iter()
+            .map(|cached_model| cached_model.hash.clone())
+            .collect()
+    }
+
+    //The lookup pins the model, it is kept out of eviction until unpin is called for the subtask.
+    pub async fn pin(hash: &str) -> Option<PathBuf> {
+        if hash.is_empty() {
+            return None;
+        }
+        let mut model_cache = Self::instance_mut().await;
+        let index = model_cache.entries.iter().position(|cached_model| cached_model.hash == hash)?;
+        let mut cached_model = model_cache.entries.remove(index)?;
+        if !cached_model.file_path.exists() {
+            model_cache.used_size -= cached_model.size;
+            return None;
+        }
+        cached_model.pins += 1;
+        let file_path = cached_model.file_path.clone();
+        model_cache.entries.push_back(cached_model);
+        Some(file_path)
+    }
+
+    pub async fn unpin(hash: &str) {
+        let mut model_cache = Self::instance_mut().await;
+        if let Some(cached_model) = model_cache.entries.iter_mut().find(|cached_model| cached_model.hash == hash) {
+            cached_model.pins = cached_model.pins.saturating_sub(1);
+        }
+        model_cache.evict(Config::now().await.model_cache_size).await;
+    }
+
+    //The inserted model is returned pinned, like a successful pin lookup.
+    pub async fn insert(hash: &str, model_path: &Path) -> Result<PathBuf, LogEntry> {
+        let file_path = match model_path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) => PathBuf::from(format!("./ModelCache/{}.{}", hash, extension)),
+            None => PathBuf::from(format!("./ModelCache/{}", hash)),
+        };
+        fs::rename(model_path, &file_path).await
+            .map_err(|err| error_entry!(IOEntry::MoveFileError(model_path.display(), file_path.display(), err)))?;
+        let size = fs::metadata(&file_path).await
+            .map_err(|err| error_entry!(IOEntry::ReadFileError(file_path.display(), err)))?
+            .len();
+        let mut model_cache = Self::instance_mut().await;
+        let mut pins = 1_usize;
+        if let Some(index) = model_cache.entries.iter().position(|cached_model| cached_model.hash == hash)
+            && let Some(cached_model) = model_cache.entries.remove(index) {
+            model_cache.used_size -= cached_model.size;
+            pins += cached_model.pins;
+        }
+        model_cache.used_size += size;
+        model_cache.entries.push_back(CachedModel { hash: hash.to_string(), file_path: file_path.clone(), size, pins });
+        model_cache.evict(Config::now().await.model_cache_size).await;
+        Ok(file_path)
+    }
+
+    //Pinned models are skipped, the cache may stay over capacity until they are released.
+    async fn evict(&mut self, capacity: u64) {
+        while self.used_size > capacity && self.entries.len() > 1 {
+            let Some(index) = self.entries.iter().position(|cached_model| cached_model.pins == 0) else {
+                break;
+            };
+            if let Some(cached_model) = self.entries.remove(index) {
+                self.used_size -= cached_model.size;
+                if let Err(err) = fs::remove_file(&cached_model.file_path).await {
+                    logging_error!(IOEntry::DeleteFileError(cached_model.file_path.display(), err));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//Kills a child process together with everything it spawned when dropped, so a cancelled or timed out
+//inference does not leave orphaned workers holding the GPU.
+pub struct ProcessTree {
+    pid: Option<u32>,
+}
+
+impl ProcessTree {
+    pub fn new(pid: Option<u32>) -> Self {
+        Self {
+            pid,
+        }
+    }
+
+    pub fn kill(&mut self) {
+        let Some(pid) = self.pid.take() else {
+            return;
+        };
+        #[cfg(target_os = "linux")]
+        unsafe {
+            libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+        }
+        #[cfg(target_os = "windows")]
+        let _ = std::process::Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status();
+    }
+
+    //Once the leader is reaped its process id and group id can be reused on any platform,
+    //so after a normal exit the tree is only forgotten, never signalled.
+    pub fn release(mut self) {
+        self.pid = None;
+    }
+}
+
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
@@ -0,0 +1,95 @@
+use crate::management::monitor::Monitor;
+use crate::management::utils::resource_usage::ResourceUsage;
+use std::future::Future;
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::sleep;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+static RUNNING_MEASUREMENTS: AtomicUsize = AtomicUsize::new(0);
+
+tokio::task_local! {
+    static PROCESS_IDS: Arc<Mutex<Vec<u32>>>;
+}
+
+//Stops the sampler and releases the running count even when the inference future is dropped.
+struct Measurement {
+    finished: Arc<AtomicBool>,
+}
+
+impl Measurement {
+    fn start() -> Self {
+        RUNNING_MEASUREMENTS.fetch_add(1, Ordering::Relaxed);
+        Self {
+            finished: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+impl Drop for Measurement {
+    fn drop(&mut self) {
+        self.finished.store(true, Ordering::Relaxed);
+        RUNNING_MEASUREMENTS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub struct ResourceSampler;
+
+impl ResourceSampler {
+    //Runs an inference and samples the peak memory of the processes it registers, backends running
+    //in the agent process itself are measured by the growth of the agent's own memory instead.
+    //That growth cannot be attributed once another subtask runs alongside, so no usage is reported then.
+    pub async fn measure<F: Future>(inference: F) -> (F::Output, Option<ResourceUsage>) {
+        let process_ids = Arc::new(Mutex::new(Vec::new()));
+        let measurement = Measurement::start();
+        let baseline = Monitor::get_process_usage(&[process::id()], false).await;
+        let sampler = {
+            let process_ids = process_ids.clone();
+            let finished = measurement.finished.clone();
+            tokio::spawn(async move {
+                let mut peak = ResourceUsage::default();
+                let mut overlapped = false;
+                while !finished.load(Ordering::Relaxed) {
+                    overlapped |= RUNNING_MEASUREMENTS.load(Ordering::Relaxed) > 1;
+                    peak = peak.peak(&Self::sample(&process_ids, &baseline).await);
+                    sleep(SAMPLE_INTERVAL).await;
+                }
+                (peak, overlapped)
+            })
+        };
+        let output = PROCESS_IDS.scope(process_ids.clone(), inference).await;
+        drop(measurement);
+        let in_process = process_ids.lock()
+            .map(|process_ids| process_ids.is_empty())
+            .unwrap_or(true);
+        let peak = match sampler.await {
+            Ok((_, true)) if in_process => None,
+            Ok((peak, _)) => Some(peak),
+            Err(_) => None,
+        };
+        (output, peak)
+    }
+
+    pub fn register(pid: u32) {
+        let _ = PROCESS_IDS.try_with(|process_ids| {
+            if let Ok(mut process_ids) = process_ids.lock() {
+                process_ids.push(pid);
+            }
+        });
+    }
+
+    async fn sample(process_ids: &Mutex<Vec<u32>>, baseline: &ResourceUsage) -> ResourceUsage {
+        let root_pids = process_ids.lock()
+            .map(|process_ids| process_ids.clone())
+            .unwrap_or_default();
+        if root_pids.is_empty() {
+            let usage = Monitor::get_process_usage(&[process::id()], false).await;
+            ResourceUsage::new((usage.ram - baseline.ram).max(0.0), (usage.vram - baseline.vram).max(0.0))
+        } else {
+            Monitor::get_process_usage(&root_pids, true).await
+        }
+    }
+}
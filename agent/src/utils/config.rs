@@ -1,3 +1,5 @@
+use crate::connection::socket::tls::TlsConfig;
+use crate::management::inference_backend::custom::CustomBackendConfig;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -24,6 +26,20 @@ pub struct Config {
     pub control_channel_timeout: u64,
     pub data_channel_timeout: u64,
     pub file_transfer_timeout: u64,
+    #[serde(default)]
+    pub token: String,
+    #[serde(default)]
+    pub tls: TlsConfig,
+    #[serde(default = "Config::default_model_cache_size")]
+    pub model_cache_size: u64, //bytes
+    #[serde(default = "Config::default_inference_concurrency")]
+    pub inference_concurrency: usize, //per GPU device
+    #[serde(default = "Config::default_inference_timeout")]
+    pub inference_timeout: u64, //seconds, zero for no limit
+    #[serde(default)]
+    pub onnxruntime_path: String,
+    #[serde(default)]
+    pub custom_backends: Vec<CustomBackendConfig>,
 }
 
 impl Config {
@@ -38,6 +54,18 @@ impl Config {
         config
     }
 
+    fn default_model_cache_size() -> u64 {
+        10 * 1024 * 1024 * 1024
+    }
+
+    fn default_inference_concurrency() -> usize {
+        1
+    }
+
+    fn default_inference_timeout() -> u64 {
+        3600
+    }
+
     pub async fn now() -> Self {
         CONFIG.read().await.clone()
     }
@@ -54,6 +82,9 @@ impl Config {
             && Config::validate_second(config.polling_interval)
             && Config::validate_second(config.data_channel_timeout)
             && Config::validate_second(config.file_transfer_timeout)
+            && config.tls.validate_client()
+            && config.inference_concurrency > 0
+            && CustomBackendConfig::validate(&config.custom_backends)
     }
 
     fn validate_mini_second(second: u64) -> bool {
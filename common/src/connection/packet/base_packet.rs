@@ -9,7 +9,7 @@ pub struct BasePacket {
 
 impl BasePacket {
     pub fn new(length: Vec<u8>, id: Vec<u8>, data: Vec<u8>) -> Self {
-        let packet_type = PacketType::parse_packet_type(&id);
+        let packet_type = PacketType::parse_packet_type(&id).unwrap_or(PacketType::BasePacket);
         Self {
             length,
             id,
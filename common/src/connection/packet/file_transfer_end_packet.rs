@@ -1,7 +1,7 @@
 use crate::connection::packet::{length_to_byte, Packet, PacketType};
-use crate::utils::DefinePacketWithoutData;
+use crate::utils::DefinePacketWithData;
 
-#[derive(DefinePacketWithoutData)]
+#[derive(DefinePacketWithData)]
 pub struct FileTransferEndPacket {
     length: Vec<u8>,
     id: Vec<u8>,
@@ -1,3 +1,4 @@
+pub mod agent_info_reject_packet;
 pub mod base_packet;
 pub mod file_body_packet;
 pub mod file_header_ack_packet;
@@ -5,6 +6,8 @@ pub mod file_header_packet;
 pub mod file_transfer_end_packet;
 pub mod file_transfer_result_packet;
 
+use crate::utils::log_entry::network::NetworkEntry;
+
 pub trait Packet: Send {
     fn as_length_byte(&self) -> &[u8];
     fn as_id_byte(&self) -> &[u8];
@@ -40,6 +43,8 @@ pub enum PacketType {
     StillProcessAckPacket,
     TaskInfoPacket,
     TaskInfoAckPacket,
+    CancelTaskPacket,
+    AgentInfoRejectPacket,
 }
 
 impl PacketType {
@@ -48,11 +53,38 @@ impl PacketType {
         id.to_be_bytes().to_vec()
     }
 
-    pub fn parse_packet_type(byte: &Vec<u8>) -> PacketType {
-        let mut byte_array = [0_u8; 8];
-        byte_array.copy_from_slice(&byte);
+    pub fn all() -> Vec<PacketType> {
+        vec![
+            PacketType::AgentInfoPacket,
+            PacketType::AgentInfoAckPacket,
+            PacketType::AlivePacket,
+            PacketType::AliveAckPacket,
+            PacketType::ControlPacket,
+            PacketType::ControlAckPacket,
+            PacketType::DataChannelPortPacket,
+            PacketType::FileBodyPacket,
+            PacketType::FileHeaderPacket,
+            PacketType::FileHeaderAckPacket,
+            PacketType::FileTransferResultPacket,
+            PacketType::FileTransferEndPacket,
+            PacketType::PerformancePacket,
+            PacketType::PerformanceAckPacket,
+            PacketType::TaskResultPacket,
+            PacketType::TaskResultAckPacket,
+            PacketType::StillProcessPacket,
+            PacketType::StillProcessAckPacket,
+            PacketType::TaskInfoPacket,
+            PacketType::TaskInfoAckPacket,
+            PacketType::CancelTaskPacket,
+            PacketType::AgentInfoRejectPacket,
+        ]
+    }
+
+    pub fn parse_packet_type(byte: &[u8]) -> Result<PacketType, NetworkEntry> {
+        let byte_array: [u8; 8] = byte.try_into()
+            .map_err(|_| NetworkEntry::InvalidPacketIdLength(byte.len()))?;
         let id = usize::from_be_bytes(byte_array);
-        match id {
+        let packet_type = match id {
             1 => PacketType::AgentInfoPacket,
             2 => PacketType::AgentInfoAckPacket,
             3 => PacketType::AlivePacket,
@@ -73,8 +105,11 @@ impl PacketType {
             18 => PacketType::StillProcessAckPacket,
             19 => PacketType::TaskInfoPacket,
             20 => PacketType::TaskInfoAckPacket,
-            _ => PacketType::BasePacket,
-        }
+            21 => PacketType::CancelTaskPacket,
+            22 => PacketType::AgentInfoRejectPacket,
+            _ => Err(NetworkEntry::UnknownPacket(id))?,
+        };
+        Ok(packet_type)
     }
 }
 
@@ -1 +1,2 @@
 pub mod socket_stream;
+pub mod tls;
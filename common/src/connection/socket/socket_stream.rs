@@ -1,9 +1,7 @@
 use crate::connection::packet::base_packet::BasePacket;
 use crate::connection::packet::Packet;
 use std::io;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
-use tokio::net::TcpStream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 pub struct SocketStream {
     read_half: ReadHalf,
@@ -11,8 +9,11 @@ pub struct SocketStream {
 }
 
 impl SocketStream {
-    pub fn new(socket: TcpStream) -> Self {
-        let (read_half, write_half) = socket.into_split();
+    pub fn new<S>(socket: S) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        let (read_half, write_half) = tokio::io::split(socket);
         Self {
             read_half: ReadHalf::new(read_half),
             write_half: WriteHalf::new(write_half),
@@ -25,13 +26,13 @@ impl SocketStream {
 }
 
 pub struct WriteHalf {
-    write_half: OwnedWriteHalf,
+    write_half: Box<dyn AsyncWrite + Send + Unpin>,
 }
 
 impl WriteHalf {
-    pub fn new(write_half: OwnedWriteHalf) -> Self {
+    pub fn new<W: AsyncWrite + Send + Unpin + 'static>(write_half: W) -> Self {
         Self {
-            write_half,
+            write_half: Box::new(write_half),
         }
     }
 
@@ -52,13 +53,13 @@ impl WriteHalf {
 }
 
 pub struct ReadHalf {
-    read_half: OwnedReadHalf,
+    read_half: Box<dyn AsyncRead + Send + Unpin>,
 }
 
 impl ReadHalf {
-    pub fn new(read_half: OwnedReadHalf) -> Self {
+    pub fn new<R: AsyncRead + Send + Unpin + 'static>(read_half: R) -> Self {
         Self {
-            read_half,
+            read_half: Box::new(read_half),
         }
     }
 
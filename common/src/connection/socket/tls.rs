@@ -0,0 +1,131 @@
+use crate::connection::socket::socket_stream::SocketStream;
+use crate::utils::log_entry::io::IOEntry;
+use crate::utils::log_entry::network::NetworkEntry;
+use crate::utils::logging::*;
+use lazy_static::lazy_static;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{ClientConfig, RootCertStore, ServerConfig};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+lazy_static! {
+    //The acceptor is rebuilt only when the TLS settings change, not on every connection.
+    static ref TLS_ACCEPTOR: Mutex<Option<(TlsConfig, TlsAcceptor)>> = Mutex::new(None);
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TlsConfig {
+    pub enable: bool,
+    #[serde(default)]
+    pub certificate: String,
+    #[serde(default)]
+    pub private_key: String,
+    #[serde(default)]
+    pub ca_certificate: String,
+}
+
+impl TlsConfig {
+    pub async fn accept(&self, tcp_stream: TcpStream, handshake_timeout: Duration) -> Result<SocketStream, LogEntry> {
+        if !self.enable {
+            return Ok(SocketStream::new(tcp_stream));
+        }
+        let acceptor = self.acceptor()?;
+        let tls_stream = timeout(handshake_timeout, acceptor.accept(tcp_stream)).await
+            .map_err(|_| warning_entry!(NetworkEntry::TlsHandshakeTimeout))?
+            .map_err(|err| warning_entry!(NetworkEntry::TlsHandshakeError(err)))?;
+        Ok(SocketStream::new(tls_stream))
+    }
+
+    pub async fn connect(&self, tcp_stream: TcpStream, server_name: &str, handshake_timeout: Duration) -> Result<SocketStream, LogEntry> {
+        if !self.enable {
+            return Ok(SocketStream::new(tcp_stream));
+        }
+        let server_name = ServerName::try_from(server_name.to_string())
+            .map_err(|err| error_entry!(NetworkEntry::TlsConfigError(err.to_string())))?;
+        let connector = TlsConnector::from(Arc::new(self.client_config()?));
+        let tls_stream = timeout(handshake_timeout, connector.connect(server_name, tcp_stream)).await
+            .map_err(|_| warning_entry!(NetworkEntry::TlsHandshakeTimeout))?
+            .map_err(|err| warning_entry!(NetworkEntry::TlsHandshakeError(err)))?;
+        Ok(SocketStream::new(tls_stream))
+    }
+
+    fn acceptor(&self) -> Result<TlsAcceptor, LogEntry> {
+        let mut tls_acceptor = TLS_ACCEPTOR.lock()
+            .map_err(|err| error_entry!(NetworkEntry::TlsConfigError(err.to_string())))?;
+        if let Some((tls_config, acceptor)) = tls_acceptor.as_ref()
+            && tls_config == self {
+            return Ok(acceptor.clone());
+        }
+        let acceptor = TlsAcceptor::from(Arc::new(self.server_config()?));
+        *tls_acceptor = Some((self.clone(), acceptor.clone()));
+        Ok(acceptor)
+    }
+
+    pub fn validate_server(&self) -> bool {
+        !self.enable || (!self.certificate.is_empty() && !self.private_key.is_empty())
+    }
+
+    pub fn validate_client(&self) -> bool {
+        !self.enable || !self.ca_certificate.is_empty()
+    }
+
+    fn server_config(&self) -> Result<ServerConfig, LogEntry> {
+        let certificates = Self::load_certificates(&self.certificate)?;
+        let private_key = Self::load_private_key(&self.private_key)?;
+        let builder = if self.ca_certificate.is_empty() {
+            ServerConfig::builder().with_no_client_auth()
+        } else {
+            let root_store = Arc::new(Self::load_root_store(&self.ca_certificate)?);
+            let verifier = WebPkiClientVerifier::builder(root_store).build()
+                .map_err(|err| error_entry!(NetworkEntry::TlsConfigError(err.to_string())))?;
+            ServerConfig::builder().with_client_cert_verifier(verifier)
+        };
+        builder.with_single_cert(certificates, private_key)
+            .map_err(|err| error_entry!(NetworkEntry::TlsConfigError(err.to_string())))
+    }
+
+    fn client_config(&self) -> Result<ClientConfig, LogEntry> {
+        let root_store = Self::load_root_store(&self.ca_certificate)?;
+        let builder = ClientConfig::builder().with_root_certificates(root_store);
+        if self.certificate.is_empty() || self.private_key.is_empty() {
+            return Ok(builder.with_no_client_auth());
+        }
+        let certificates = Self::load_certificates(&self.certificate)?;
+        let private_key = Self::load_private_key(&self.private_key)?;
+        builder.with_client_auth_cert(certificates, private_key)
+            .map_err(|err| error_entry!(NetworkEntry::TlsConfigError(err.to_string())))
+    }
+
+    fn load_certificates(path: &str) -> Result<Vec<CertificateDer<'static>>, LogEntry> {
+        let file = File::open(path)
+            .map_err(|err| error_entry!(IOEntry::ReadFileError(Path::new(path).display(), err)))?;
+        rustls_pemfile::certs(&mut BufReader::new(file))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| error_entry!(IOEntry::ReadFileError(Path::new(path).display(), err)))
+    }
+
+    fn load_private_key(path: &str) -> Result<PrivateKeyDer<'static>, LogEntry> {
+        let file = File::open(path)
+            .map_err(|err| error_entry!(IOEntry::ReadFileError(Path::new(path).display(), err)))?;
+        rustls_pemfile::private_key(&mut BufReader::new(file))
+            .map_err(|err| error_entry!(IOEntry::ReadFileError(Path::new(path).display(), err)))?
+            .ok_or(error_entry!(NetworkEntry::TlsConfigError(format!("No private key found in {path}"))))
+    }
+
+    fn load_root_store(path: &str) -> Result<RootCertStore, LogEntry> {
+        let mut root_store = RootCertStore::empty();
+        for certificate in Self::load_certificates(path)? {
+            root_store.add(certificate)
+                .map_err(|err| error_entry!(NetworkEntry::TlsConfigError(err.to_string())))?;
+        }
+        Ok(root_store)
+    }
+}
@@ -0,0 +1,56 @@
+use crate::management::gpu_probe::{cuda_environment, GpuProbe, ProbeFuture};
+use crate::management::utils::gpu_device::GpuDevice;
+use crate::management::utils::gpu_usage::GpuUsage;
+use std::collections::HashMap;
+
+//Reports fixed devices and readings, so scheduling can be exercised without GPU hardware.
+pub struct FakeProbe {
+    devices: Vec<GpuDevice>,
+    usages: Vec<GpuUsage>,
+    process_vram: HashMap<u32, u64>,
+}
+
+impl FakeProbe {
+    pub fn new(devices: Vec<GpuDevice>) -> Self {
+        let usages = devices.iter()
+            .map(|gpu_device| GpuUsage::new(gpu_device.index, 0.0, 0.0))
+            .collect();
+        Self {
+            devices,
+            usages,
+            process_vram: HashMap::new(),
+        }
+    }
+
+    pub fn with_usages(mut self, usages: Vec<GpuUsage>) -> Self {
+        self.usages = usages;
+        self
+    }
+
+    pub fn with_process_vram(mut self, process_vram: HashMap<u32, u64>) -> Self {
+        self.process_vram = process_vram;
+        self
+    }
+}
+
+impl GpuProbe for FakeProbe {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn devices(&self) -> Result<Vec<GpuDevice>, String> {
+        Ok(self.devices.clone())
+    }
+
+    fn usages(&self) -> ProbeFuture<'_, Vec<GpuUsage>> {
+        Box::pin(async { Ok(self.usages.clone()) })
+    }
+
+    fn process_vram(&self) -> ProbeFuture<'_, HashMap<u32, u64>> {
+        Box::pin(async { Ok(self.process_vram.clone()) })
+    }
+
+    fn device_environment(&self, index: u32) -> Vec<(&'static str, String)> {
+        cuda_environment(index)
+    }
+}
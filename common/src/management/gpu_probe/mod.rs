@@ -0,0 +1,54 @@
+#[cfg(any(test, feature = "fake-probe"))]
+pub mod fake;
+pub mod none;
+pub mod nvidia_smi;
+pub mod nvml;
+pub mod rocm_smi;
+
+use crate::management::gpu_probe::none::NoneProbe;
+use crate::management::gpu_probe::nvidia_smi::NvidiaSmiProbe;
+use crate::management::gpu_probe::nvml::NvmlProbe;
+use crate::management::gpu_probe::rocm_smi::RocmSmiProbe;
+use crate::management::utils::gpu_device::GpuDevice;
+use crate::management::utils::gpu_usage::GpuUsage;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub type ProbeFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+pub trait GpuProbe: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn devices(&self) -> Result<Vec<GpuDevice>, String>;
+
+    fn usages(&self) -> ProbeFuture<'_, Vec<GpuUsage>>;
+
+    //VRAM used by every process on any device, keyed by pid, in bytes.
+    fn process_vram(&self) -> ProbeFuture<'_, HashMap<u32, u64>>;
+
+    //Environment variables restricting a child process to a single device.
+    fn device_environment(&self, _index: u32) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+}
+
+//NVML is preferred over parsing command line tools, machines without a supported GPU fall back to CPU only.
+pub fn detect() -> Arc<dyn GpuProbe> {
+    if let Ok(probe) = NvmlProbe::new() {
+        return Arc::new(probe);
+    }
+    if NvidiaSmiProbe::available() {
+        return Arc::new(NvidiaSmiProbe);
+    }
+    if RocmSmiProbe::available() {
+        return Arc::new(RocmSmiProbe);
+    }
+    Arc::new(NoneProbe)
+}
+
+//CUDA enumerates the fastest device first unless told otherwise, the probes report devices in PCI bus order.
+pub fn cuda_environment(index: u32) -> Vec<(&'static str, String)> {
+    vec![("CUDA_DEVICE_ORDER", "PCI_BUS_ID".to_string()), ("CUDA_VISIBLE_DEVICES", index.to_string())]
+}
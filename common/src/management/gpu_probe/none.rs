@@ -0,0 +1,24 @@
+use crate::management::gpu_probe::{GpuProbe, ProbeFuture};
+use crate::management::utils::gpu_device::GpuDevice;
+use crate::management::utils::gpu_usage::GpuUsage;
+use std::collections::HashMap;
+
+pub struct NoneProbe;
+
+impl GpuProbe for NoneProbe {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn devices(&self) -> Result<Vec<GpuDevice>, String> {
+        Ok(Vec::new())
+    }
+
+    fn usages(&self) -> ProbeFuture<'_, Vec<GpuUsage>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn process_vram(&self) -> ProbeFuture<'_, HashMap<u32, u64>> {
+        Box::pin(async { Ok(HashMap::new()) })
+    }
+}
@@ -0,0 +1,106 @@
+use crate::management::gpu_probe::{cuda_environment, GpuProbe, ProbeFuture};
+use crate::management::utils::gpu_device::GpuDevice;
+use crate::management::utils::gpu_usage::GpuUsage;
+use std::collections::HashMap;
+use std::process::Command;
+use tokio::process::Command as AsyncCommand;
+
+pub struct NvidiaSmiProbe;
+
+impl NvidiaSmiProbe {
+    pub fn available() -> bool {
+        Command::new("nvidia-smi")
+            .arg("-L")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    fn parse_line(line: &str) -> Result<(u32, String, u64), String> {
+        let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+        match fields.as_slice() {
+            [index, value, memory] => {
+                let index = index.parse::<u32>()
+                    .map_err(|_| "Fail to parse gpu information.".to_string())?;
+                let memory = memory.parse::<u64>()
+                    .map_err(|_| "Fail to parse gpu information.".to_string())?;
+                Ok((index, value.to_string(), memory * 1_048_576_u64))
+            }
+            _ => Err("Fail to parse gpu information.".to_string()),
+        }
+    }
+
+    async fn query_usages() -> Result<Vec<GpuUsage>, String> {
+        let gpu_usages = AsyncCommand::new("nvidia-smi")
+            .arg("--query-gpu=index,utilization.gpu,memory.used")
+            .arg("--format=csv,noheader,nounits")
+            .output()
+            .await
+            .map_err(|_| "Fail to get gpu information.".to_string())?;
+        String::from_utf8_lossy(&gpu_usages.stdout)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let (index, gpu_usage, vram_used) = Self::parse_line(line)?;
+                let gpu_usage = gpu_usage.parse::<f64>()
+                    .map_err(|_| "Fail to parse gpu information.".to_string())?;
+                Ok(GpuUsage::new(index, gpu_usage, vram_used as f64))
+            })
+            .collect()
+    }
+
+    async fn query_process_vram() -> Result<HashMap<u32, u64>, String> {
+        let process_vram = AsyncCommand::new("nvidia-smi")
+            .arg("--query-compute-apps=pid,used_memory")
+            .arg("--format=csv,noheader,nounits")
+            .output()
+            .await
+            .map_err(|_| "Fail to get gpu information.".to_string())?;
+        let process_vram = String::from_utf8_lossy(&process_vram.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (pid, used_memory) = line.split_once(',')?;
+                let pid = pid.trim().parse::<u32>().ok()?;
+                let used_memory = used_memory.trim().parse::<u64>().ok()?;
+                Some((pid, used_memory * 1_048_576_u64))
+            })
+            .fold(HashMap::new(), |mut process_vram, (pid, used_memory)| {
+                *process_vram.entry(pid).or_insert(0) += used_memory;
+                process_vram
+            });
+        Ok(process_vram)
+    }
+}
+
+impl GpuProbe for NvidiaSmiProbe {
+    fn name(&self) -> &'static str {
+        "nvidia-smi"
+    }
+
+    fn devices(&self) -> Result<Vec<GpuDevice>, String> {
+        let gpu_devices = Command::new("nvidia-smi")
+            .arg("--query-gpu=index,name,memory.total")
+            .arg("--format=csv,noheader,nounits")
+            .output()
+            .map_err(|_| "Fail to get gpu information.".to_string())?;
+        String::from_utf8_lossy(&gpu_devices.stdout)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let (index, name, vram_total) = Self::parse_line(line)?;
+                Ok(GpuDevice::new(index, name, vram_total as f64))
+            })
+            .collect()
+    }
+
+    fn usages(&self) -> ProbeFuture<'_, Vec<GpuUsage>> {
+        Box::pin(Self::query_usages())
+    }
+
+    fn process_vram(&self) -> ProbeFuture<'_, HashMap<u32, u64>> {
+        Box::pin(Self::query_process_vram())
+    }
+
+    fn device_environment(&self, index: u32) -> Vec<(&'static str, String)> {
+        cuda_environment(index)
+    }
+}
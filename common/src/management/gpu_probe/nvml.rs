@@ -0,0 +1,89 @@
+use crate::management::gpu_probe::{cuda_environment, GpuProbe, ProbeFuture};
+use crate::management::utils::gpu_device::GpuDevice;
+use crate::management::utils::gpu_usage::GpuUsage;
+use nvml_wrapper::enums::device::UsedGpuMemory;
+use nvml_wrapper::Nvml;
+use std::collections::HashMap;
+
+pub struct NvmlProbe {
+    nvml: Nvml,
+}
+
+impl NvmlProbe {
+    //Fails when the NVIDIA driver library cannot be loaded.
+    pub fn new() -> Result<Self, String> {
+        let nvml = Nvml::init()
+            .map_err(|err| err.to_string())?;
+        Ok(Self {
+            nvml,
+        })
+    }
+
+    fn query_usages(&self) -> Result<Vec<GpuUsage>, String> {
+        let device_count = self.nvml.device_count()
+            .map_err(|err| err.to_string())?;
+        (0..device_count)
+            .map(|index| {
+                let device = self.nvml.device_by_index(index)
+                    .map_err(|err| err.to_string())?;
+                let utilization = device.utilization_rates()
+                    .map_err(|err| err.to_string())?;
+                let memory_info = device.memory_info()
+                    .map_err(|err| err.to_string())?;
+                Ok(GpuUsage::new(index, utilization.gpu as f64, memory_info.used as f64))
+            })
+            .collect()
+    }
+
+    fn query_process_vram(&self) -> Result<HashMap<u32, u64>, String> {
+        let device_count = self.nvml.device_count()
+            .map_err(|err| err.to_string())?;
+        let mut process_vram = HashMap::new();
+        for index in 0..device_count {
+            let device = self.nvml.device_by_index(index)
+                .map_err(|err| err.to_string())?;
+            let processes = device.running_compute_processes()
+                .map_err(|err| err.to_string())?;
+            for process in processes {
+                if let UsedGpuMemory::Used(used_memory) = process.used_gpu_memory {
+                    *process_vram.entry(process.pid).or_insert(0) += used_memory;
+                }
+            }
+        }
+        Ok(process_vram)
+    }
+}
+
+impl GpuProbe for NvmlProbe {
+    fn name(&self) -> &'static str {
+        "nvml"
+    }
+
+    fn devices(&self) -> Result<Vec<GpuDevice>, String> {
+        let device_count = self.nvml.device_count()
+            .map_err(|err| err.to_string())?;
+        (0..device_count)
+            .map(|index| {
+                let device = self.nvml.device_by_index(index)
+                    .map_err(|err| err.to_string())?;
+                let name = device.name()
+                    .map_err(|err| err.to_string())?;
+                let memory_info = device.memory_info()
+                    .map_err(|err| err.to_string())?;
+                Ok(GpuDevice::new(index, name, memory_info.total as f64))
+            })
+            .collect()
+    }
+
+    fn usages(&self) -> ProbeFuture<'_, Vec<GpuUsage>> {
+        Box::pin(async { self.query_usages() })
+    }
+
+    fn process_vram(&self) -> ProbeFuture<'_, HashMap<u32, u64>> {
+        Box::pin(async { self.query_process_vram() })
+    }
+
+    fn device_environment(&self, index: u32) -> Vec<(&'static str, String)> {
+        cuda_environment(index)
+    }
+}
@@ -0,0 +1,121 @@
+use crate::management::gpu_probe::{GpuProbe, ProbeFuture};
+use crate::management::utils::gpu_device::GpuDevice;
+use crate::management::utils::gpu_usage::GpuUsage;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::process::Command;
+use tokio::process::Command as AsyncCommand;
+
+type Card = Map<String, Value>;
+
+pub struct RocmSmiProbe;
+
+impl RocmSmiProbe {
+    pub fn available() -> bool {
+        Command::new("rocm-smi")
+            .arg("--showid")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    //rocm-smi reports every device as a "cardN" object, field names differ slightly between releases.
+    fn parse_cards(stdout: &[u8]) -> Result<Vec<(u32, Card)>, String> {
+        let value = serde_json::from_slice::<Value>(stdout)
+            .map_err(|_| "Fail to parse gpu information.".to_string())?;
+        let mut cards: Vec<(u32, Card)> = value.as_object()
+            .ok_or("Fail to parse gpu information.".to_string())?
+            .iter()
+            .filter_map(|(key, card)| {
+                let index = key.strip_prefix("card")?.parse::<u32>().ok()?;
+                Some((index, card.as_object()?.clone()))
+            })
+            .collect();
+        cards.sort_by_key(|(index, _)| *index);
+        Ok(cards)
+    }
+
+    fn field<'a>(card: &'a Card, names: &[&str]) -> Option<&'a str> {
+        card.iter()
+            .find(|(key, _)| names.iter().any(|name| key.eq_ignore_ascii_case(name)))
+            .and_then(|(_, value)| value.as_str())
+    }
+
+    fn number(card: &Card, names: &[&str]) -> f64 {
+        Self::field(card, names)
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .unwrap_or_default()
+    }
+
+    async fn query_usages() -> Result<Vec<GpuUsage>, String> {
+        let gpu_usages = AsyncCommand::new("rocm-smi")
+            .args(["--showuse", "--showmeminfo", "vram", "--json"])
+            .output()
+            .await
+            .map_err(|_| "Fail to get gpu information.".to_string())?;
+        let gpu_usages = Self::parse_cards(&gpu_usages.stdout)?.into_iter()
+            .map(|(index, card)| {
+                let gpu_usage = Self::number(&card, &["GPU use (%)"]);
+                let vram_used = Self::number(&card, &["VRAM Total Used Memory (B)"]);
+                GpuUsage::new(index, gpu_usage, vram_used)
+            })
+            .collect();
+        Ok(gpu_usages)
+    }
+
+    //Every process is reported as "name, gpu count, vram used, sdma used, cu occupancy".
+    async fn query_process_vram() -> Result<HashMap<u32, u64>, String> {
+        let process_vram = AsyncCommand::new("rocm-smi")
+            .args(["--showpids", "--json"])
+            .output()
+            .await
+            .map_err(|_| "Fail to get gpu information.".to_string())?;
+        let value = serde_json::from_slice::<Value>(&process_vram.stdout)
+            .map_err(|_| "Fail to parse gpu information.".to_string())?;
+        let process_vram = value.get("system")
+            .and_then(|system| system.as_object())
+            .map(|system| system.iter()
+                .filter_map(|(key, process)| {
+                    let pid = key.strip_prefix("PID")?.parse::<u32>().ok()?;
+                    let used_memory = process.as_str()?.split(',').nth(2)?.trim().parse::<u64>().ok()?;
+                    Some((pid, used_memory))
+                })
+                .collect())
+            .unwrap_or_default();
+        Ok(process_vram)
+    }
+}
+
+impl GpuProbe for RocmSmiProbe {
+    fn name(&self) -> &'static str {
+        "rocm-smi"
+    }
+
+    fn devices(&self) -> Result<Vec<GpuDevice>, String> {
+        let gpu_devices = Command::new("rocm-smi")
+            .args(["--showproductname", "--showmeminfo", "vram", "--json"])
+            .output()
+            .map_err(|_| "Fail to get gpu information.".to_string())?;
+        let gpu_devices = Self::parse_cards(&gpu_devices.stdout)?.into_iter()
+            .map(|(index, card)| {
+                let name = Self::field(&card, &["Card series", "Card model"])
+                    .unwrap_or_default()
+                    .to_string();
+                let vram_total = Self::number(&card, &["VRAM Total Memory (B)"]);
+                GpuDevice::new(index, name, vram_total)
+            })
+            .collect();
+        Ok(gpu_devices)
+    }
+
+    fn usages(&self) -> ProbeFuture<'_, Vec<GpuUsage>> {
+        Box::pin(Self::query_usages())
+    }
+
+    fn process_vram(&self) -> ProbeFuture<'_, HashMap<u32, u64>> {
+        Box::pin(Self::query_process_vram())
+    }
+
+    fn device_environment(&self, index: u32) -> Vec<(&'static str, String)> {
+        vec![("HIP_VISIBLE_DEVICES", index.to_string())]
+    }
+}
@@ -1,2 +1,3 @@
 pub mod utils;
+pub mod gpu_probe;
 pub mod monitor;
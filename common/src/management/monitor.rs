@@ -1,11 +1,14 @@
+use crate::management::gpu_probe;
+use crate::management::gpu_probe::GpuProbe;
 use crate::management::utils::agent_information::AgentInformation;
 use crate::management::utils::performance::Performance;
+use crate::management::utils::resource_usage::ResourceUsage;
 use crate::utils::log_entry::system::SystemEntry;
 use crate::utils::logging::*;
 use lazy_static::lazy_static;
-use std::process::Command;
-use sysinfo::System;
-use tokio::process::Command as AsyncCommand;
+use std::collections::HashSet;
+use std::sync::Arc;
+use sysinfo::{Pid, ProcessesToUpdate, System};
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use tokio::time::sleep;
 
@@ -14,6 +17,7 @@ lazy_static! {
 }
 
 pub struct Monitor {
+    probe: Arc<dyn GpuProbe>,
     information: AgentInformation,
     performance: Performance,
     terminate: bool,
@@ -21,8 +25,10 @@ pub struct Monitor {
 
 impl Monitor {
     fn new() -> Self {
+        let probe = gpu_probe::detect();
         Self {
-            information: Self::system_info(),
+            information: Self::system_info(probe.as_ref()),
+            probe,
             performance: Performance::default(),
             terminate: false,
         }
@@ -37,37 +43,52 @@ impl Monitor {
     }
 
     pub async fn run() {
+        let probe = Self::instance().await.probe.name();
+        logging_console!(information_entry!(SystemEntry::GpuProbeSelected(probe.to_string())));
         tokio::spawn(async {
             Self::update_performance().await;
         });
         logging_console!(information_entry!(SystemEntry::Online));
     }
 
+    pub async fn use_probe(probe: Arc<dyn GpuProbe>) {
+        let information = Self::system_info(probe.as_ref());
+        let mut monitor = Self::instance_mut().await;
+        monitor.information = information;
+        monitor.probe = probe;
+    }
+
     pub async fn terminate() {
         Self::instance_mut().await.terminate = true;
     }
 
-    fn system_info() -> AgentInformation {
+    fn system_info(probe: &dyn GpuProbe) -> AgentInformation {
         let sys = System::new_all();
-        let host_name = System::host_name().expect("Fail to get system information.");
+        let host_name = System::host_name().unwrap_or_default();
+        let long_os_version = System::long_os_version().unwrap_or_default();
+        let kernel_version = System::kernel_version().unwrap_or_default();
         let os_name = if cfg!(target_os = "windows") {
-            let long_os_version = System::long_os_version().expect("Fail to get system information.");
-            let kernel_version = System::kernel_version().expect("Fail to get system information.");
             format!("{} build {}", long_os_version, kernel_version)
         } else if cfg!(target_os = "linux") {
-            let long_os_version = System::long_os_version().expect("Fail to get system information.");
-            let kernel_version = System::kernel_version().expect("Fail to get system information.");
             format!("{} {}", long_os_version, kernel_version)
         } else {
-            System::long_os_version().expect("Fail to get system information.")
+            long_os_version
         };
-        let cpu = sys.cpus().get(0).map(|cpu| cpu.brand())
-            .expect("Fail to get system information.")
-            .to_string();
-        let cores = sys.physical_core_count().expect("Fail to get system information.");
+        let cpu = sys.cpus().first()
+            .map(|cpu| cpu.brand().to_string())
+            .unwrap_or_default();
+        let cores = sys.physical_core_count().unwrap_or(sys.cpus().len());
         let ram = sys.total_memory() as f64;
-        let gpu = Self::get_gpu_name().expect("Fail to get system information.");
-        let vram = Self::get_vram_total().expect("Fail to get system information.") as f64;
+        //A failing probe leaves a CPU only agent instead of stopping the program.
+        let gpus = probe.devices().unwrap_or_else(|err| {
+            logging_console!(warning_entry!(SystemEntry::GpuProbeError(err)));
+            Vec::new()
+        });
+        let gpu = gpus.iter()
+            .map(|gpu_device| gpu_device.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let vram = gpus.iter().map(|gpu_device| gpu_device.vram).sum::<f64>();
         AgentInformation {
             host_name,
             os_name,
@@ -76,55 +97,34 @@ impl Monitor {
             ram,
             gpu,
             vram,
+            gpus,
+            backends: Vec::new(),
         }
     }
 
-    fn get_gpu_name() -> Result<String, String> {
-        let gpu_name = Command::new("nvidia-smi")
-            .arg("--query-gpu=name")
-            .arg("--format=csv,noheader")
-            .output()
-            .map_err(|_| "Fail to get gpu information.".to_string())?;
-        let gpu_name = String::from_utf8_lossy(&gpu_name.stdout).trim().to_string();
-        Ok(gpu_name)
-    }
-
-    fn get_vram_total() -> Result<u64, String> {
-        let vram_total = Command::new("nvidia-smi")
-            .arg("--query-gpu=memory.total")
-            .arg("--format=csv,noheader,nounits")
-            .output()
-            .map_err(|_| "Fail to get gpu information.".to_string())?;
-        let vram_total = String::from_utf8_lossy(&vram_total.stdout).trim().to_string()
-            .parse::<u64>()
-            .map_err(|_| "Fail to parse gpu information.".to_string())?;
-        Ok(vram_total * 1_048_576_u64)
-    }
-
-    async fn get_gpu_usage() -> Result<u64, String> {
-        let gpu_usage = AsyncCommand::new("nvidia-smi")
-            .arg("--query-gpu=utilization.gpu")
-            .arg("--format=csv,noheader,nounits")
-            .output()
-            .await
-            .map_err(|_| "Fail to get gpu information.".to_string())?;
-        let gpu_usage = String::from_utf8_lossy(&gpu_usage.stdout).trim().to_string()
-            .parse::<u64>()
-            .map_err(|_| "Fail to parse gpu information.".to_string())?;
-        Ok(gpu_usage)
-    }
-
-    async fn get_vram_used() -> Result<u64, String> {
-        let vram_used = AsyncCommand::new("nvidia-smi")
-            .arg("--query-gpu=memory.used")
-            .arg("--format=csv,noheader,nounits")
-            .output()
-            .await
-            .map_err(|_| "Fail to get gpu information.".to_string())?;
-        let vram_used = String::from_utf8_lossy(&vram_used.stdout).trim().to_string()
-            .parse::<u64>()
-            .map_err(|_| "Fail to parse gpu information.".to_string())?;
-        Ok(vram_used * 1_048_576_u64)
+    pub async fn get_process_usage(root_pids: &[u32], descendants: bool) -> ResourceUsage {
+        let mut system = System::new();
+        system.refresh_processes(ProcessesToUpdate::All, true);
+        let mut process_tree: HashSet<Pid> = root_pids.iter().map(|pid| Pid::from_u32(*pid)).collect();
+        let mut size = 0_usize;
+        while descendants && process_tree.len() != size {
+            size = process_tree.len();
+            for (pid, process) in system.processes() {
+                if process.parent().is_some_and(|parent| process_tree.contains(&parent)) {
+                    process_tree.insert(*pid);
+                }
+            }
+        }
+        let ram = process_tree.iter()
+            .filter_map(|pid| system.process(*pid))
+            .map(|process| process.memory() as f64)
+            .sum::<f64>();
+        let probe = Self::instance().await.probe.clone();
+        let vram = probe.process_vram().await.unwrap_or_default().into_iter()
+            .filter(|(pid, _)| process_tree.contains(&Pid::from_u32(*pid)))
+            .map(|(_, used_memory)| used_memory as f64)
+            .sum::<f64>();
+        ResourceUsage::new(ram, vram)
     }
 
     async fn update_performance() {
@@ -135,9 +135,9 @@ impl Monitor {
                 .map(|core| core.cpu_usage() as f64)
                 .sum::<f64>() / system.cpus().len() as f64;
             let ram_used = system.used_memory() as f64;
-            let gpu_usage = Self::get_gpu_usage().await.unwrap_or_default() as f64;
-            let vram_used = Self::get_vram_used().await.unwrap_or_default() as f64;
-            Self::instance_mut().await.performance = Performance::new(cpu_usage, ram_used, gpu_usage, vram_used);
+            let probe = Self::instance().await.probe.clone();
+            let gpu_usages = probe.usages().await.unwrap_or_default();
+            Self::instance_mut().await.performance = Performance::new(cpu_usage, ram_used, gpu_usages);
             sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL).await;
         }
     }
@@ -149,4 +149,40 @@ impl Monitor {
     pub async fn get_performance() -> Performance {
         Self::instance().await.performance.clone()
     }
+
+    pub async fn device_environment(index: u32) -> Vec<(&'static str, String)> {
+        Self::instance().await.probe.device_environment(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::management::gpu_probe::fake::FakeProbe;
+    use crate::management::utils::gpu_device::GpuDevice;
+    use crate::management::utils::gpu_usage::GpuUsage;
+    use std::collections::HashMap;
+    use std::process;
+
+    //The monitor is shared, so everything depending on the probe is checked in a single test.
+    #[tokio::test]
+    async fn monitor_reports_devices_and_usage_from_probe() {
+        let devices = vec![
+            GpuDevice::new(0, "Fake GPU 0".to_string(), 8.0e9),
+            GpuDevice::new(1, "Fake GPU 1".to_string(), 16.0e9),
+        ];
+        let process_vram = HashMap::from([(process::id(), 2_000_000_000), (u32::MAX, 1_000_000_000)]);
+        let probe = FakeProbe::new(devices)
+            .with_usages(vec![GpuUsage::new(0, 50.0, 4.0e9), GpuUsage::new(1, 0.0, 0.0)])
+            .with_process_vram(process_vram);
+        Monitor::use_probe(Arc::new(probe)).await;
+        let information = Monitor::get_system_info().await;
+        assert_eq!(information.gpus.len(), 2);
+        assert_eq!(information.gpu, "Fake GPU 0, Fake GPU 1");
+        assert_eq!(information.vram, 24.0e9);
+        let resource_usage = Monitor::get_process_usage(&[process::id()], false).await;
+        assert_eq!(resource_usage.vram, 2.0e9);
+        assert!(resource_usage.ram > 0.0);
+        assert_eq!(Monitor::device_environment(1).await, vec![("CUDA_DEVICE_ORDER", "PCI_BUS_ID".to_string()), ("CUDA_VISIBLE_DEVICES", "1".to_string())]);
+    }
 }
@@ -1,4 +1,6 @@
+use crate::management::utils::backend_capability::BackendCapability;
 use crate::management::utils::format::format_bytes;
+use crate::management::utils::gpu_device::GpuDevice;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
@@ -11,6 +13,10 @@ pub struct AgentInformation {
     pub ram: f64,
     pub gpu: String,
     pub vram: f64,
+    #[serde(default)]
+    pub gpus: Vec<GpuDevice>,
+    #[serde(default)]
+    pub backends: Vec<BackendCapability>,
 }
 
 impl Display for AgentInformation {
@@ -0,0 +1,20 @@
+use crate::management::utils::agent_information::AgentInformation;
+use crate::management::utils::capability::Capability;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AgentRegistration {
+    pub token: String,
+    pub information: AgentInformation,
+    pub capability: Capability,
+    #[serde(default)]
+    pub cached_models: Vec<String>,
+    #[serde(default = "AgentRegistration::default_concurrency")]
+    pub concurrency: usize,
+}
+
+impl AgentRegistration {
+    fn default_concurrency() -> usize {
+        1
+    }
+}
@@ -5,6 +5,7 @@ use std::cmp::Ordering;
 pub enum AgentState {
     None,
     ProcessTask,
+    CollectResult,
     Idle(u64),
     CreateDataChannel,
     Terminate,
@@ -27,7 +28,7 @@ impl AgentState {
         match self {
             AgentState::Terminate => 4,
             AgentState::CreateDataChannel => 3,
-            AgentState::ProcessTask | AgentState::Idle(_) => 2,
+            AgentState::ProcessTask | AgentState::CollectResult | AgentState::Idle(_) => 2,
             AgentState::None => 1,
         }
     }
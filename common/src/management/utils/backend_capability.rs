@@ -0,0 +1,25 @@
+use crate::management::utils::inference_argument::ModelType;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackendCapability {
+    pub name: String,
+    pub model_type: ModelType,
+    pub image: bool,
+    pub video: bool,
+}
+
+impl BackendCapability {
+    pub fn new(name: &str, model_type: ModelType, image: bool, video: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            model_type,
+            image,
+            video,
+        }
+    }
+
+    pub fn supports(&self, model_type: &ModelType, image: bool) -> bool {
+        self.model_type == *model_type && if image { self.image } else { self.video }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::connection::packet::PacketType;
+use crate::management::utils::inference_argument::ModelType;
+use serde::{Deserialize, Serialize};
+
+pub const PROTOCOL_VERSION: u32 = 2;
+pub const MAX_CHUNK_SIZE: usize = 1_048_576;
+pub const TRANSFER_WINDOW_SIZE: usize = 16;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Compression {
+    None,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Capability {
+    pub protocol_version: u32,
+    pub packet_types: Vec<usize>,
+    pub model_types: Vec<ModelType>,
+    pub compression: Vec<Compression>,
+    pub max_chunk_size: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Negotiation {
+    pub model_types: Vec<ModelType>,
+    pub compression: Compression,
+    pub chunk_size: usize,
+}
+
+impl Capability {
+    pub fn new(model_types: Vec<ModelType>) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            packet_types: PacketType::all().into_iter().map(|packet_type| packet_type as usize).collect(),
+            model_types,
+            compression: vec![Compression::None],
+            max_chunk_size: MAX_CHUNK_SIZE,
+        }
+    }
+
+    pub fn negotiate(&self, peer: &Capability) -> Result<Negotiation, String> {
+        if self.protocol_version != peer.protocol_version {
+            return Err(format!("Protocol version {} is not compatible with {}", peer.protocol_version, self.protocol_version));
+        }
+        let missing_packet_types: Vec<usize> = self.packet_types.iter()
+            .filter(|packet_type| !peer.packet_types.contains(packet_type))
+            .cloned()
+            .collect();
+        if !missing_packet_types.is_empty() {
+            return Err(format!("Packet types {:?} are not supported", missing_packet_types));
+        }
+        //Custom model types are declared by agents, so management cannot know their names in advance.
+        let mut model_types: Vec<ModelType> = self.model_types.iter()
+            .filter(|model_type| matches!(model_type, ModelType::Custom(_)) || peer.model_types.contains(model_type))
+            .cloned()
+            .collect();
+        for model_type in &peer.model_types {
+            if matches!(model_type, ModelType::Custom(_)) && !model_types.contains(model_type) {
+                model_types.push(model_type.clone());
+            }
+        }
+        if model_types.is_empty() {
+            return Err("No model type is supported by both sides".to_string());
+        }
+        let compression = self.compression.iter()
+            .find(|compression| peer.compression.contains(compression))
+            .cloned()
+            .ok_or("No compression is supported by both sides".to_string())?;
+        let chunk_size = self.max_chunk_size.min(peer.max_chunk_size);
+        if chunk_size == 0 {
+            return Err("Chunk size must be greater than zero".to_string());
+        }
+        Ok(Negotiation {
+            model_types,
+            compression,
+            chunk_size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiation_keeps_shared_features() {
+        let management = Capability::new(vec![ModelType::Ultralytics, ModelType::YOLOv7, ModelType::Onnx]);
+        let mut agent = Capability::new(vec![ModelType::Ultralytics, ModelType::Onnx, ModelType::Custom("detector".to_string())]);
+        agent.max_chunk_size = 65_536;
+        let negotiation = management.negotiate(&agent).expect("capabilities are compatible");
+        assert_eq!(negotiation.model_types, vec![ModelType::Ultralytics, ModelType::Onnx, ModelType::Custom("detector".to_string())]);
+        assert_eq!(negotiation.compression, Compression::None);
+        assert_eq!(negotiation.chunk_size, 65_536);
+    }
+
+    #[test]
+    fn negotiation_is_symmetric_in_chunk_size() {
+        let management = Capability::new(vec![ModelType::Ultralytics]);
+        let mut agent = Capability::new(vec![ModelType::Ultralytics]);
+        agent.max_chunk_size = 4_096;
+        assert_eq!(management.negotiate(&agent).map(|negotiation| negotiation.chunk_size), Ok(4_096));
+        assert_eq!(agent.negotiate(&management).map(|negotiation| negotiation.chunk_size), Ok(4_096));
+    }
+
+    #[test]
+    fn negotiation_rejects_protocol_mismatch() {
+        let management = Capability::new(vec![ModelType::Ultralytics]);
+        let mut agent = Capability::new(vec![ModelType::Ultralytics]);
+        agent.protocol_version = PROTOCOL_VERSION + 1;
+        assert!(management.negotiate(&agent).is_err());
+    }
+
+    #[test]
+    fn negotiation_rejects_missing_packet_types() {
+        let management = Capability::new(vec![ModelType::Ultralytics]);
+        let mut agent = Capability::new(vec![ModelType::Ultralytics]);
+        agent.packet_types.pop();
+        assert!(management.negotiate(&agent).is_err());
+    }
+
+    #[test]
+    fn negotiation_rejects_disjoint_model_types() {
+        let management = Capability::new(vec![ModelType::Ultralytics]);
+        let agent = Capability::new(vec![ModelType::YOLOv4]);
+        assert!(management.negotiate(&agent).is_err());
+    }
+
+    #[test]
+    fn negotiation_rejects_zero_chunk_size() {
+        let management = Capability::new(vec![ModelType::Ultralytics]);
+        let mut agent = Capability::new(vec![ModelType::Ultralytics]);
+        agent.max_chunk_size = 0;
+        assert!(management.negotiate(&agent).is_err());
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Detection {
+    pub bbox: [f64; 4],
+    pub confidence: f64,
+    pub class_id: u32,
+    pub class_name: String,
+    pub track_id: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FrameDetection {
+    pub frame: usize,
+    pub detections: Vec<Detection>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DetectionFile {
+    pub width: u32,
+    pub height: u32,
+    pub frame_count: usize,
+    pub names: BTreeMap<u32, String>,
+    pub frames: Vec<FrameDetection>,
+}
@@ -3,14 +3,38 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Clone)]
 pub struct FileHeader {
     pub file_name: String,
+    pub file_size: u64,
+    pub chunk_size: usize,
     pub packet_count: usize,
+    pub window_size: usize,
 }
 
 impl FileHeader {
-    pub fn new(file_name: String, packet_count: usize) -> Self {
+    pub fn new(file_name: String, file_size: u64, chunk_size: usize, window_size: usize) -> Self {
         Self {
             file_name,
-            packet_count,
+            file_size,
+            chunk_size,
+            packet_count: file_size.div_ceil(chunk_size as u64) as usize,
+            window_size,
         }
     }
+
+    //Sizes come from the peer, so they are bounded before any buffer is allocated from them.
+    pub fn validate(&self, max_chunk_size: usize, max_window_size: usize) -> Result<(), String> {
+        if self.chunk_size == 0 || self.chunk_size > max_chunk_size {
+            return Err(format!("Chunk size {} is outside 1 to {}", self.chunk_size, max_chunk_size));
+        }
+        if self.window_size == 0 || self.window_size > max_window_size {
+            return Err(format!("Window size {} is outside 1 to {}", self.window_size, max_window_size));
+        }
+        if self.packet_count as u64 != self.file_size.div_ceil(self.chunk_size as u64) {
+            return Err(format!("Packet count {} does not match the file size", self.packet_count));
+        }
+        Ok(())
+    }
+
+    pub fn window_end(&self, window_start: usize) -> usize {
+        (window_start + self.window_size).min(self.packet_count)
+    }
 }
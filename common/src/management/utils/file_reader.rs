@@ -0,0 +1,101 @@
+use crate::management::utils::capability::MAX_CHUNK_SIZE;
+use crate::management::utils::file_header::FileHeader;
+use crate::utils::log_entry::io::IOEntry;
+use crate::utils::logging::*;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+pub struct FileReader {
+    file: File,
+    file_path: PathBuf,
+    file_size: u64,
+    chunk_size: usize,
+    packet_count: usize,
+    hasher: Sha256,
+    hashed: usize, //chunks already fed to the hasher, always in file order
+    sha256: Option<String>,
+    window: BTreeMap<usize, Vec<u8>>,
+}
+
+impl FileReader {
+    pub async fn open(file_path: &Path, chunk_size: usize) -> Result<Self, LogEntry> {
+        let file = File::open(file_path).await
+            .map_err(|err| error_entry!(IOEntry::ReadFileError(file_path.display(), err)))?;
+        let file_size = file.metadata().await
+            .map_err(|err| error_entry!(IOEntry::ReadFileError(file_path.display(), err)))?
+            .len();
+        let packet_count = file_size.div_ceil(chunk_size as u64) as usize;
+        let sha256 = (packet_count == 0).then(|| format!("{:x}", Sha256::digest(b"")));
+        Ok(Self {
+            file,
+            file_path: file_path.to_path_buf(),
+            file_size,
+            chunk_size,
+            packet_count,
+            hasher: Sha256::new(),
+            hashed: 0,
+            sha256,
+            window: BTreeMap::new(),
+        })
+    }
+
+    pub async fn sha256_of(file_path: &Path) -> Result<String, LogEntry> {
+        let mut file = File::open(file_path).await
+            .map_err(|err| error_entry!(IOEntry::ReadFileError(file_path.display(), err)))?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0_u8; MAX_CHUNK_SIZE];
+        loop {
+            let bytes_read = file.read(&mut buffer).await
+                .map_err(|err| error_entry!(IOEntry::ReadFileError(file_path.display(), err)))?;
+            if bytes_read == 0 {
+                return Ok(format!("{:x}", hasher.finalize()));
+            }
+            hasher.update(&buffer[..bytes_read]);
+        }
+    }
+
+    pub fn file_header(&self, file_name: String, window_size: usize) -> FileHeader {
+        FileHeader::new(file_name, self.file_size, self.chunk_size, window_size)
+    }
+
+    //Every chunk carries its sequence number and CRC32, the file digest is built while the windows are read.
+    pub async fn read_window(&mut self, window_start: usize, window_end: usize) -> Result<(), LogEntry> {
+        self.window.clear();
+        let offset = (window_start * self.chunk_size) as u64;
+        self.file.seek(SeekFrom::Start(offset)).await
+            .map_err(|err| error_entry!(IOEntry::ReadFileError(self.file_path.display(), err)))?;
+        for sequence_number in window_start..window_end {
+            let offset = (sequence_number * self.chunk_size) as u64;
+            let length = (self.file_size - offset).min(self.chunk_size as u64) as usize;
+            let header_size = size_of::<usize>() + size_of::<u32>();
+            let mut data = sequence_number.to_be_bytes().to_vec();
+            data.resize(header_size + length, 0);
+            self.file.read_exact(&mut data[header_size..]).await
+                .map_err(|err| error_entry!(IOEntry::ReadFileError(self.file_path.display(), err)))?;
+            let checksum = crc32fast::hash(&data[header_size..]);
+            data[size_of::<usize>()..header_size].copy_from_slice(&checksum.to_be_bytes());
+            if sequence_number == self.hashed {
+                self.hasher.update(&data[header_size..]);
+                self.hashed += 1;
+                if self.hashed == self.packet_count {
+                    self.sha256 = Some(format!("{:x}", self.hasher.finalize_reset()));
+                }
+            }
+            self.window.insert(sequence_number, data);
+        }
+        Ok(())
+    }
+
+    //Sent with every window end, it holds the file digest once the last chunk has been read.
+    pub fn trailer(&self) -> Vec<u8> {
+        self.sha256.clone().unwrap_or_default().into_bytes()
+    }
+
+    pub fn chunk(&self, sequence_number: usize) -> Option<&Vec<u8>> {
+        self.window.get(&sequence_number)
+    }
+}
@@ -0,0 +1,208 @@
+use crate::management::utils::capability::TRANSFER_WINDOW_SIZE;
+use crate::management::utils::file_header::FileHeader;
+use crate::utils::log_entry::io::IOEntry;
+use crate::utils::log_entry::misc::MiscEntry;
+use crate::utils::logging::*;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+pub struct FileWriter {
+    file: File,
+    file_path: PathBuf,
+    file_header: FileHeader,
+    window_start: usize,
+    window: BTreeMap<usize, Vec<u8>>, //chunks of the current window, hashed in order once it is complete
+    hasher: Sha256,
+    sha256: Option<String>, //digest announced by the sender with the last window
+}
+
+impl FileWriter {
+    pub async fn create(saved_folder: &Path, file_header: FileHeader, max_chunk_size: usize) -> Result<Self, LogEntry> {
+        file_header.validate(max_chunk_size, TRANSFER_WINDOW_SIZE)
+            .map_err(|reason| error_entry!(MiscEntry::InvalidFileHeader(reason)))?;
+        let file_path = saved_folder.join(&file_header.file_name);
+        let file = OpenOptions::new().write(true).create(true).truncate(true).open(&file_path).await
+            .map_err(|err| error_entry!(IOEntry::CreateFileError(file_path.display(), err)))?;
+        Ok(Self {
+            file,
+            file_path,
+            file_header,
+            window_start: 0,
+            window: BTreeMap::new(),
+            hasher: Sha256::new(),
+            sha256: None,
+        })
+    }
+
+    pub async fn write_chunk(&mut self, data: &[u8]) -> Result<bool, LogEntry> {
+        let header_size = size_of::<usize>() + size_of::<u32>();
+        if data.len() < header_size {
+            Err(error_entry!(MiscEntry::InvalidPacket))?;
+        }
+        let (chunk_header, file_body) = data.split_at(header_size);
+        let (sequence_bytes, checksum_bytes) = chunk_header.split_at(size_of::<usize>());
+        let sequence_number = usize::from_be_bytes(sequence_bytes.try_into()
+            .map_err(|_| error_entry!(MiscEntry::InvalidPacket))?);
+        let checksum = u32::from_be_bytes(checksum_bytes.try_into()
+            .map_err(|_| error_entry!(MiscEntry::InvalidPacket))?);
+        if sequence_number >= self.file_header.packet_count || file_body.len() > self.file_header.chunk_size {
+            Err(error_entry!(MiscEntry::InvalidPacket))?;
+        }
+        if checksum != crc32fast::hash(file_body) {
+            return Ok(false);
+        }
+        let window_end = self.file_header.window_end(self.window_start);
+        if !(self.window_start..window_end).contains(&sequence_number) || self.window.contains_key(&sequence_number) {
+            return Ok(true);
+        }
+        let offset = (sequence_number * self.file_header.chunk_size) as u64;
+        self.file.seek(SeekFrom::Start(offset)).await
+            .map_err(|err| error_entry!(IOEntry::WriteFileError(self.file_path.display(), err)))?;
+        self.file.write_all(file_body).await
+            .map_err(|err| error_entry!(IOEntry::WriteFileError(self.file_path.display(), err)))?;
+        self.window.insert(sequence_number, file_body.to_vec());
+        Ok(true)
+    }
+
+    pub fn end_window(&mut self, trailer: &[u8]) -> Option<Vec<usize>> {
+        if !trailer.is_empty() {
+            self.sha256 = Some(String::from_utf8_lossy(trailer).to_string());
+        }
+        let window_end = self.file_header.window_end(self.window_start);
+        let missing_chunks: Vec<usize> = (self.window_start..window_end)
+            .filter(|sequence_number| !self.window.contains_key(sequence_number))
+            .collect();
+        if !missing_chunks.is_empty() {
+            return Some(missing_chunks);
+        }
+        for file_body in self.window.values() {
+            self.hasher.update(file_body);
+        }
+        self.window.clear();
+        self.window_start = window_end;
+        None
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.window_start >= self.file_header.packet_count
+    }
+
+    pub async fn finish(mut self) -> Result<(), LogEntry> {
+        self.file.flush().await
+            .map_err(|err| error_entry!(IOEntry::WriteFileError(self.file_path.display(), err)))?;
+        let file_size = self.file.metadata().await
+            .map_err(|err| error_entry!(IOEntry::ReadFileError(self.file_path.display(), err)))?
+            .len();
+        if !self.is_complete() || file_size != self.file_header.file_size {
+            Err(error_entry!(MiscEntry::FileSizeMismatch(self.file_header.file_size, file_size)))?;
+        }
+        let sha256 = format!("{:x}", self.hasher.finalize());
+        //Empty files have no window, so no digest is ever announced for them.
+        let expected = match self.sha256 {
+            Some(expected) => expected,
+            None if self.file_header.packet_count == 0 => return Ok(()),
+            None => String::new(),
+        };
+        if sha256 != expected {
+            Err(error_entry!(MiscEntry::FileHashMismatch(expected, sha256)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::management::utils::capability::MAX_CHUNK_SIZE;
+    use std::env;
+    use uuid::Uuid;
+
+    const CHUNK_SIZE: usize = 1_024;
+
+    fn chunk(data: &[u8], sequence_number: usize) -> Vec<u8> {
+        let file_body = &data[sequence_number * CHUNK_SIZE..((sequence_number + 1) * CHUNK_SIZE).min(data.len())];
+        chunk_with_checksum(sequence_number, crc32fast::hash(file_body), file_body)
+    }
+
+    fn chunk_with_checksum(sequence_number: usize, checksum: u32, file_body: &[u8]) -> Vec<u8> {
+        let mut packet = sequence_number.to_be_bytes().to_vec();
+        packet.extend_from_slice(&checksum.to_be_bytes());
+        packet.extend_from_slice(file_body);
+        packet
+    }
+
+    fn sha256(data: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(data))
+    }
+
+    async fn file_writer(saved_folder: &Path, data: &[u8]) -> FileWriter {
+        let file_header = FileHeader::new("file.bin".to_string(), data.len() as u64, CHUNK_SIZE, TRANSFER_WINDOW_SIZE);
+        match FileWriter::create(saved_folder, file_header, MAX_CHUNK_SIZE).await {
+            Ok(file_writer) => file_writer,
+            Err(_) => panic!("Unable to create file writer"),
+        }
+    }
+
+    fn saved_folder() -> PathBuf {
+        let saved_folder = env::temp_dir().join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&saved_folder).expect("Unable to create test folder");
+        saved_folder
+    }
+
+    #[tokio::test]
+    async fn corrupted_chunk_is_requested_again() {
+        let saved_folder = saved_folder();
+        let data: Vec<u8> = (0..2_500).map(|byte| byte as u8).collect();
+        let mut file_writer = file_writer(&saved_folder, &data).await;
+        let corrupted = chunk_with_checksum(1, crc32fast::hash(b"corrupted"), &data[CHUNK_SIZE..2 * CHUNK_SIZE]);
+        assert!(matches!(file_writer.write_chunk(&corrupted).await, Ok(false)));
+        for sequence_number in [0, 2] {
+            assert!(matches!(file_writer.write_chunk(&chunk(&data, sequence_number)).await, Ok(true)));
+        }
+        assert_eq!(file_writer.end_window(sha256(&data).as_bytes()), Some(vec![1]));
+        assert!(matches!(file_writer.write_chunk(&chunk(&data, 1)).await, Ok(true)));
+        assert_eq!(file_writer.end_window(&[]), None);
+        assert!(file_writer.is_complete());
+        assert!(file_writer.finish().await.is_ok());
+        assert_eq!(std::fs::read(saved_folder.join("file.bin")).ok(), Some(data));
+        let _ = std::fs::remove_dir_all(&saved_folder);
+    }
+
+    #[tokio::test]
+    async fn hash_mismatch_fails_the_transfer() {
+        let saved_folder = saved_folder();
+        let data: Vec<u8> = (0..2_500).map(|byte| (byte * 7) as u8).collect();
+        let mut file_writer = file_writer(&saved_folder, &data).await;
+        for sequence_number in 0..3 {
+            assert!(matches!(file_writer.write_chunk(&chunk(&data, sequence_number)).await, Ok(true)));
+        }
+        assert_eq!(file_writer.end_window(sha256(b"other file").as_bytes()), None);
+        assert!(file_writer.finish().await.is_err());
+        let _ = std::fs::remove_dir_all(&saved_folder);
+    }
+
+    #[tokio::test]
+    async fn missing_hash_fails_the_transfer() {
+        let saved_folder = saved_folder();
+        let data = vec![1_u8; 100];
+        let mut file_writer = file_writer(&saved_folder, &data).await;
+        assert!(matches!(file_writer.write_chunk(&chunk(&data, 0)).await, Ok(true)));
+        assert_eq!(file_writer.end_window(&[]), None);
+        assert!(file_writer.finish().await.is_err());
+        let _ = std::fs::remove_dir_all(&saved_folder);
+    }
+
+    #[tokio::test]
+    async fn out_of_range_chunk_is_rejected() {
+        let saved_folder = saved_folder();
+        let data = vec![1_u8; 100];
+        let mut file_writer = file_writer(&saved_folder, &data).await;
+        assert!(file_writer.write_chunk(&chunk_with_checksum(5, crc32fast::hash(&data), &data)).await.is_err());
+        assert!(file_writer.write_chunk(&[0; 4]).await.is_err());
+        let _ = std::fs::remove_dir_all(&saved_folder);
+    }
+}
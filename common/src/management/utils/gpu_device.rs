@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GpuDevice {
+    pub index: u32,
+    pub name: String,
+    pub vram: f64, //bytes
+}
+
+impl GpuDevice {
+    pub fn new(index: u32, name: String, vram: f64) -> Self {
+        Self {
+            index,
+            name,
+            vram,
+        }
+    }
+}
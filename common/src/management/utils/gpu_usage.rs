@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct GpuUsage {
+    pub index: u32,
+    pub gpu: f64, //percent
+    pub vram: f64, //bytes
+}
+
+impl GpuUsage {
+    pub fn new(index: u32, gpu: f64, vram: f64) -> Self {
+        Self {
+            index,
+            gpu,
+            vram,
+        }
+    }
+}
@@ -10,13 +10,17 @@ pub struct InferenceArgument {
     pub imgsz: usize,
     pub batch: usize,
     pub conf: f32,
+    #[serde(default)]
+    pub timeout: Option<u64>, //seconds
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub enum ModelType {
     Ultralytics,
     YOLOv4,
     YOLOv7,
+    Onnx,
+    Custom(String),
 }
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
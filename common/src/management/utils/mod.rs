@@ -1,9 +1,18 @@
 pub mod agent_information;
+pub mod agent_registration;
 pub mod agent_state;
+pub mod backend_capability;
+pub mod capability;
+pub mod detection_file;
 pub mod file_header;
+pub mod file_reader;
 pub mod file_transfer_result;
+pub mod file_writer;
 pub mod format;
+pub mod gpu_device;
+pub mod gpu_usage;
 pub mod inference_argument;
 pub mod performance;
+pub mod resource_usage;
 pub mod task_info;
 pub mod task_result;
@@ -1,23 +1,33 @@
 use crate::management::utils::agent_information::AgentInformation;
 use crate::management::utils::format::format_bytes;
+use crate::management::utils::gpu_usage::GpuUsage;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Performance {
     pub cpu: f64,
     pub ram: f64,
     pub gpu: f64,
     pub vram: f64,
+    #[serde(default)]
+    pub gpus: Vec<GpuUsage>,
 }
 
 impl Performance {
-    pub fn new(cpu: f64, ram: f64, gpu: f64, vram: f64) -> Self {
+    pub fn new(cpu: f64, ram: f64, gpus: Vec<GpuUsage>) -> Self {
+        let gpu = if gpus.is_empty() {
+            0.0
+        } else {
+            gpus.iter().map(|gpu_usage| gpu_usage.gpu).sum::<f64>() / gpus.len() as f64
+        };
+        let vram = gpus.iter().map(|gpu_usage| gpu_usage.vram).sum::<f64>();
         Self {
             cpu,
             ram,
             gpu,
             vram,
+            gpus,
         }
     }
 
@@ -27,17 +37,34 @@ impl Performance {
             ram: 0.0,
             gpu: 0.0,
             vram: 0.0,
+            gpus: Vec::new(),
         }
     }
 
     pub fn calc_residual_usage(agent_information: &AgentInformation, realtime_performance: &Performance) -> Performance {
+        let gpus = agent_information.gpus.iter()
+            .map(|gpu_device| {
+                let gpu_usage = realtime_performance.gpus.iter()
+                    .find(|gpu_usage| gpu_usage.index == gpu_device.index);
+                let gpu = gpu_usage.map(|gpu_usage| gpu_usage.gpu).unwrap_or_default();
+                let vram = gpu_usage.map(|gpu_usage| gpu_usage.vram).unwrap_or_default();
+                GpuUsage::new(gpu_device.index, 100_f64 - gpu, gpu_device.vram - vram)
+            })
+            .collect();
         Self {
             cpu: 100_f64 - realtime_performance.cpu,
             ram: agent_information.ram - realtime_performance.ram,
             gpu: 100_f64 - realtime_performance.gpu,
             vram: agent_information.vram - realtime_performance.vram,
+            gpus,
         }
     }
+
+    pub fn device_vram(&self, index: u32) -> Option<f64> {
+        self.gpus.iter()
+            .find(|gpu_usage| gpu_usage.index == index)
+            .map(|gpu_usage| gpu_usage.vram)
+    }
 }
 
 impl Display for Performance {
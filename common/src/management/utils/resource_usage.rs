@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct ResourceUsage {
+    pub ram: f64, //bytes
+    pub vram: f64, //bytes
+}
+
+impl ResourceUsage {
+    pub fn new(ram: f64, vram: f64) -> Self {
+        Self {
+            ram,
+            vram,
+        }
+    }
+
+    pub fn peak(&self, other: &ResourceUsage) -> Self {
+        Self {
+            ram: self.ram.max(other.ram),
+            vram: self.vram.max(other.vram),
+        }
+    }
+}
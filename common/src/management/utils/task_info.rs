@@ -1,22 +1,38 @@
 use crate::management::utils::inference_argument::InferenceArgument;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct TaskInfo {
     pub uuid: Uuid,
+    #[serde(default)]
+    pub subtask_uuid: Uuid,
     pub model_file_name: String,
+    #[serde(default)]
+    pub model_hash: String,
     pub media_file_name: String,
     pub inference_argument: InferenceArgument,
+    #[serde(default)]
+    pub estimated_vram: f64,
+    #[serde(default)]
+    pub device: Option<u32>,
+    #[serde(skip)]
+    pub model_path: Option<PathBuf>, //resolved by the agent once the model is pinned or received
 }
 
 impl TaskInfo {
-    pub fn new(uuid: Uuid, model_file_name: String, image_file_name: String, inference_argument: InferenceArgument) -> Self {
+    pub fn new(uuid: Uuid, subtask_uuid: Uuid, model_file_name: String, model_hash: String, image_file_name: String, inference_argument: InferenceArgument, estimated_vram: f64) -> Self {
         Self {
             uuid,
+            subtask_uuid,
             model_file_name,
+            model_hash,
             media_file_name: image_file_name,
             inference_argument,
+            estimated_vram,
+            device: None,
+            model_path: None,
         }
     }
 }
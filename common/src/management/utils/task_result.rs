@@ -1,18 +1,24 @@
+use crate::management::utils::resource_usage::ResourceUsage;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct TaskResult {
+    pub subtask_uuid: Uuid,
     pub status: Result<(), String>,
+    #[serde(default)]
+    pub stderr: String,
+    #[serde(default)]
+    pub resource_usage: Option<ResourceUsage>,
 }
 
 impl TaskResult {
-    pub fn new(result: Result<(), String>) -> Self {
+    pub fn new(subtask_uuid: Uuid, result: Result<(), String>, stderr: String, resource_usage: Option<ResourceUsage>) -> Self {
         Self {
+            subtask_uuid,
             status: result,
+            stderr,
+            resource_usage,
         }
     }
-
-    pub fn into(self) -> Result<(), String> {
-        self.status
-    }
 }
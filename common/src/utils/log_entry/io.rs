@@ -34,6 +34,8 @@ pub enum IOEntry<'a> {
     SerdeSerializeError(SerdeJsonError),
     #[error("Failed to deserialize data: {0}")]
     SerdeDeserializeError(SerdeJsonError),
+    #[error("Task store operation failed: {0}")]
+    TaskStoreError(String),
 }
 
 impl From<IOEntry<'_>> for String {
@@ -10,6 +10,14 @@ pub enum MiscEntry {
     InvalidPacket,
     #[error("Wrong packet deliver order")]
     WrongDeliverOrder,
+    #[error("File size mismatch, expected {0} bytes but received {1} bytes")]
+    FileSizeMismatch(u64, u64),
+    #[error("Chunk checksum mismatch, requesting retransmission")]
+    ChunkChecksumMismatch,
+    #[error("File hash mismatch, expected {0} but computed {1}")]
+    FileHashMismatch(String, String),
+    #[error("Invalid file header: {0}")]
+    InvalidFileHeader(String),
 }
 
 impl From<MiscEntry> for String {
@@ -21,12 +21,30 @@ pub enum NetworkEntry {
     EstablishConnectionError(IOError),
     #[error("Receive unexpected packet")]
     UnexpectedPacket,
+    #[error("Receive unknown packet id: {0}")]
+    UnknownPacket(usize),
+    #[error("Receive packet id of invalid length: {0} bytes")]
+    InvalidPacketIdLength(usize),
+    #[error("Incompatible peer: {0}")]
+    IncompatiblePeer(String),
     #[error("agent side disconnect")]
     AgentDisconnect,
     #[error("management side disconnect")]
     ManagementDisconnect,
     #[error("Failed to destroy instance")]
     DestroyInstanceError,
+    #[error("Invalid TLS configuration: {0}")]
+    TlsConfigError(String),
+    #[error("TLS handshake failed: {0}")]
+    TlsHandshakeError(IOError),
+    #[error("TLS handshake timeout")]
+    TlsHandshakeTimeout,
+    #[error("Agent registration rejected: {0}")]
+    AgentRejected(String),
+    #[error("Registration rejected by management: {0}")]
+    RegistrationRejected(String),
+    #[error("Registration rejected by agent: {0}")]
+    RejectedByAgent(String),
 }
 
 impl From<NetworkEntry> for String {
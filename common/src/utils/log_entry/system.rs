@@ -43,6 +43,10 @@ pub enum SystemEntry {
     ChildProcessError(String),
     #[error("Task panic while execution: {0}")]
     TaskPanickedError(JoinError),
+    #[error("GPU telemetry provided by {0}")]
+    GpuProbeSelected(String),
+    #[error("Fail to probe GPU, continue without GPU: {0}")]
+    GpuProbeError(String),
 }
 
 impl From<SystemEntry> for String {
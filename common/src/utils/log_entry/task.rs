@@ -9,8 +9,26 @@ pub enum TaskEntry {
     TaskAssignError(Uuid),
     #[error("Task {0} does not exist")]
     TaskDoesNotExist(Uuid),
+    #[error("Task {0} cancelled")]
+    TaskCancelled(Uuid),
+    #[error("Task {0} restored from task store")]
+    TaskRestored(Uuid),
     #[error("Error occur while agent processing task: {0}")]
     AgentProcessingError(String),
+    #[error("Inference backend {0} is unavailable on this agent")]
+    BackendUnavailable(String),
+    #[error("ONNX Runtime error: {0}")]
+    OnnxRuntimeError(String),
+    #[error("Unable to process image: {0}")]
+    ImageProcessError(String),
+    #[error("Task {0}, retrying subtask, attempt {1}")]
+    SubtaskRetry(Uuid, usize),
+    #[error("Task {0}, subtask {1} already has a result")]
+    DuplicateSubtaskResult(Uuid, Uuid),
+    #[error("Inference exceeded the time limit of {0} seconds")]
+    InferenceTimeout(u64),
+    #[error("Inference cancelled before it finished")]
+    InferenceCancelled,
 }
 
 impl From<TaskEntry> for String {
@@ -0,0 +1,50 @@
+import json
+from pathlib import Path
+
+
+class DetectionCollector:
+    def __init__(self):
+        self.names = {}
+        self.width = 0
+        self.height = 0
+        self.frames = []
+
+    def on_predict_postprocess_end(self, predictor):
+        """
+        Collect boxes, classes, confidences and track IDs of the frames in the current batch.
+
+        :param predictor: The predictor which finished post-processing the batch.
+        """
+        for result in predictor.results:
+            self.names = {int(class_id): name for class_id, name in result.names.items()}
+            self.height, self.width = result.orig_shape[:2]
+            detections = []
+            boxes = result.boxes
+            if boxes is not None:
+                track_ids = boxes.id.int().tolist() if boxes.id is not None else [None] * len(boxes)
+                for bbox, confidence, class_id, track_id in zip(boxes.xyxy.tolist(), boxes.conf.tolist(),
+                                                                 boxes.cls.int().tolist(), track_ids):
+                    detections.append({
+                        'bbox': bbox,
+                        'confidence': confidence,
+                        'class_id': class_id,
+                        'class_name': self.names.get(class_id, str(class_id)),
+                        'track_id': track_id,
+                    })
+            self.frames.append({'frame': len(self.frames), 'detections': detections})
+
+    def save(self, save_path: Path):
+        """
+        Write the collected detections to a JSON file.
+
+        :param save_path: Path of the JSON file to write.
+        """
+        detection = {
+            'width': self.width,
+            'height': self.height,
+            'frame_count': len(self.frames),
+            'names': self.names,
+            'frames': self.frames,
+        }
+        with open(save_path, 'w') as file:
+            json.dump(detection, file)
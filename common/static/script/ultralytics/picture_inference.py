@@ -3,6 +3,7 @@ import logging
 import sys
 from pathlib import Path
 
+from detection_collector import DetectionCollector
 from ultralytics.models.yolo.detect import DetectionPredictor
 from ultralytics.utils import LOGGER
 from ultralytics.utils import callbacks
@@ -14,6 +15,7 @@ class PictureInference:
         self.picture_path = picture_path
         self.save_path = save_path
         self.args = kwargs
+        self.collector = DetectionCollector()
         if not kwargs.get("verbose", False):
             LOGGER.setLevel(logging.NOTSET)
 
@@ -33,6 +35,7 @@ class PictureInference:
             args = {**default_args, **self.args}
             callback = callbacks.get_default_callbacks()
             predictor = DetectionPredictor(overrides=args, _callbacks=callback)
+            predictor.add_callback("on_predict_postprocess_end", self.collector.on_predict_postprocess_end)
             predictor.setup_model(model=self.model_path)
             predictor.save_dir = self.save_path
             predictor.predict_cli(source=self.picture_path)
@@ -63,6 +66,8 @@ class PictureInference:
             predicted_picture = self.save_path / f"{picture_filename}.{picture_suffix}"
             if not predicted_picture.exists():
                 self.panic("Unexpected error: Predicted picture does not exist.")
+
+            self.collector.save(self.save_path / f"{self.picture_path.name}.json")
         except Exception as e:
             self.panic(f"An unexpected error occurred: {e}")
 
@@ -72,6 +77,8 @@ if __name__ == "__main__":
         parser = argparse.ArgumentParser(description='picture Inference Script',
                                          usage='%(prog)s mode model picture save imgsz conf verbose',
                                          formatter_class=argparse.RawTextHelpFormatter)
+        parser.add_argument('mode', type=str, choices=['predict', 'track'],
+                            help="Mode of operation, pictures are always predicted")
         parser.add_argument('model_path', type=Path, help='Path to the model')
         parser.add_argument('picture_path', type=Path, help='Path to the input picture')
         parser.add_argument('save_path', type=Path, help='Path to save the output')
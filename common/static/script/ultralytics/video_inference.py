@@ -4,6 +4,7 @@ import sys
 from pathlib import Path
 
 import ffmpeg
+from detection_collector import DetectionCollector
 from ultralytics.models.yolo.detect import DetectionPredictor
 from ultralytics.trackers import register_tracker
 from ultralytics.utils import LOGGER
@@ -17,6 +18,7 @@ class VideoInference:
         self.video_path = video_path
         self.save_path = save_path
         self.args = kwargs
+        self.collector = DetectionCollector()
         if not kwargs.get("verbose", False):
             LOGGER.setLevel(logging.NOTSET)
 
@@ -54,6 +56,7 @@ class VideoInference:
             args = {**default_args, **self.args}
             callback = callbacks.get_default_callbacks()
             predictor = DetectionPredictor(overrides=args, _callbacks=callback)
+            predictor.add_callback("on_predict_postprocess_end", self.collector.on_predict_postprocess_end)
             predictor.setup_model(model=self.model_path)
             predictor.save_dir = self.save_path
             predictor.predict_cli(source=self.video_path)
@@ -78,6 +81,7 @@ class VideoInference:
             callback = callbacks.get_default_callbacks()
             predictor = DetectionPredictor(overrides=args, _callbacks=callback)
             register_tracker(predictor, False)
+            predictor.add_callback("on_predict_postprocess_end", self.collector.on_predict_postprocess_end)
             predictor.setup_model(model=self.model_path)
             predictor.save_dir = self.save_path
             predictor.predict_cli(source=self.video_path)
@@ -156,6 +160,8 @@ class VideoInference:
             else:
                 final_output_video = self.save_path / f"{video_filename}.{video_suffix}"
                 predicted_video.rename(final_output_video)
+
+            self.collector.save(self.save_path / f"{self.video_path.name}.json")
         except Exception as e:
             self.panic(f"An unexpected error occurred: {e}")
 
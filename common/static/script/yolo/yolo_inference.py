@@ -0,0 +1,227 @@
+import argparse
+import json
+import math
+import sys
+from pathlib import Path
+
+import cv2
+import numpy as np
+import torch
+
+PICTURE_SUFFIXES = {'.png', '.jpg', '.jpeg'}
+VIDEO_SUFFIXES = {'.mp4'}
+
+
+class YoloInference:
+    def __init__(self, repository: Path, model_path: Path, media_path: Path, save_path: Path, **kwargs):
+        self.repository = repository
+        self.model_path = model_path
+        self.media_path = media_path
+        self.save_path = save_path
+        self.imgsz = math.ceil(kwargs.get('imgsz', 640) / 32) * 32
+        self.conf = kwargs.get('conf', 0.25)
+        self.iou = kwargs.get('iou', 0.45)
+        self.batch = max(kwargs.get('batch', 1), 1)
+        self.verbose = kwargs.get('verbose', False)
+        self.device = torch.device('cuda:0' if torch.cuda.is_available() else 'cpu')
+        self.half = self.device.type != 'cpu'
+        self.model = None
+        self.names = {}
+        self.width = 0
+        self.height = 0
+        self.frames = []
+        if not self.repository.exists():
+            self.panic(f"Repository {self.repository} does not exist.")
+        sys.path.insert(0, str(self.repository.resolve()))
+
+    def load_model(self):
+        """
+        Load the model from the repository and return it together with its class names.
+
+        :return: Tuple of the model in evaluation mode and a list of class names.
+        """
+        raise NotImplementedError
+
+    def non_max_suppression(self, prediction: torch.Tensor) -> list:
+        """
+        Filter the raw model prediction with the repository's non-maximum suppression.
+
+        :param prediction: Raw prediction of a batch.
+        :return: List of (n, 6) tensors in letterboxed xyxy, confidence, class order.
+        """
+        raise NotImplementedError
+
+    def letterbox(self, image: np.ndarray):
+        """
+        Resize an image to a square of imgsz while keeping its aspect ratio.
+
+        :param image: BGR image.
+        :return: Tuple of the padded image, the resize ratio and the (left, top) padding.
+        """
+        height, width = image.shape[:2]
+        ratio = min(self.imgsz / height, self.imgsz / width)
+        resized_width, resized_height = round(width * ratio), round(height * ratio)
+        resized = cv2.resize(image, (resized_width, resized_height), interpolation=cv2.INTER_LINEAR)
+        left = (self.imgsz - resized_width) // 2
+        top = (self.imgsz - resized_height) // 2
+        padded = cv2.copyMakeBorder(resized, top, self.imgsz - resized_height - top, left,
+                                    self.imgsz - resized_width - left, cv2.BORDER_CONSTANT, value=(114, 114, 114))
+        return padded, ratio, (left, top)
+
+    def predict(self, images: list) -> list:
+        """
+        Run the model on a batch of images, draw the detections on them and collect the results.
+
+        :param images: List of BGR images.
+        :return: List of the annotated images.
+        """
+        letterboxed = [self.letterbox(image) for image in images]
+        batch = np.stack([padded[:, :, ::-1].transpose(2, 0, 1) for padded, _, _ in letterboxed])
+        tensor = torch.from_numpy(np.ascontiguousarray(batch)).to(self.device)
+        tensor = (tensor.half() if self.half else tensor.float()) / 255.0
+        with torch.no_grad():
+            prediction = self.model(tensor)[0]
+        outputs = self.non_max_suppression(prediction)
+        annotated = []
+        for image, (_, ratio, (left, top)), output in zip(images, letterboxed, outputs):
+            height, width = image.shape[:2]
+            self.height, self.width = height, width
+            detections = []
+            if output is not None:
+                for *xyxy, confidence, class_id in output.tolist():
+                    x1 = min(max((xyxy[0] - left) / ratio, 0), width)
+                    y1 = min(max((xyxy[1] - top) / ratio, 0), height)
+                    x2 = min(max((xyxy[2] - left) / ratio, 0), width)
+                    y2 = min(max((xyxy[3] - top) / ratio, 0), height)
+                    class_id = int(class_id)
+                    class_name = self.names.get(class_id, str(class_id))
+                    detections.append({
+                        'bbox': [x1, y1, x2, y2],
+                        'confidence': confidence,
+                        'class_id': class_id,
+                        'class_name': class_name,
+                        'track_id': None,
+                    })
+                    self.draw(image, (x1, y1, x2, y2), f"{class_name} {confidence:.2f}")
+            self.frames.append({'frame': len(self.frames), 'detections': detections})
+            annotated.append(image)
+        return annotated
+
+    @staticmethod
+    def draw(image: np.ndarray, bbox: tuple, label: str):
+        """
+        Draw a bounding box and its label on an image.
+
+        :param image: BGR image to draw on.
+        :param bbox: Bounding box in xyxy order.
+        :param label: Text drawn above the bounding box.
+        """
+        x1, y1, x2, y2 = (int(value) for value in bbox)
+        cv2.rectangle(image, (x1, y1), (x2, y2), (0, 255, 0), 2)
+        cv2.putText(image, label, (x1, max(y1 - 4, 12)), cv2.FONT_HERSHEY_SIMPLEX, 0.5, (0, 255, 0), 1, cv2.LINE_AA)
+
+    def inference_picture(self):
+        """
+        Inference a single picture and save the annotated picture.
+        """
+        image = cv2.imread(str(self.media_path))
+        if image is None:
+            self.panic(f"Unable to read picture {self.media_path}.")
+        annotated = self.predict([image])[0]
+        if not cv2.imwrite(str(self.save_path / self.media_path.name), annotated):
+            self.panic("Unexpected error: Predicted picture cannot be written.")
+
+    def inference_video(self):
+        """
+        Inference every frame of a video in batches and save the annotated video.
+        """
+        capture = cv2.VideoCapture(str(self.media_path))
+        if not capture.isOpened():
+            self.panic(f"Unable to read video {self.media_path}.")
+        fps = capture.get(cv2.CAP_PROP_FPS) or 30
+        width = int(capture.get(cv2.CAP_PROP_FRAME_WIDTH))
+        height = int(capture.get(cv2.CAP_PROP_FRAME_HEIGHT))
+        writer = cv2.VideoWriter(str(self.save_path / self.media_path.name), cv2.VideoWriter_fourcc(*'mp4v'),
+                                 fps, (width, height))
+        batch = []
+        while True:
+            success, frame = capture.read()
+            if success:
+                batch.append(frame)
+            if batch and (not success or len(batch) == self.batch):
+                for annotated in self.predict(batch):
+                    writer.write(annotated)
+                batch = []
+            if not success:
+                break
+        capture.release()
+        writer.release()
+
+    def save_detection(self):
+        """
+        Write the collected detections to a JSON file.
+        """
+        detection = {
+            'width': self.width,
+            'height': self.height,
+            'frame_count': len(self.frames),
+            'names': self.names,
+            'frames': self.frames,
+        }
+        with open(self.save_path / f"{self.media_path.name}.json", 'w') as file:
+            json.dump(detection, file)
+
+    @staticmethod
+    def panic(*args, **kwargs):
+        """
+        Print an error message to stderr and exit the program.
+
+        :param args: Arguments to print.
+        :param kwargs: Keyword arguments to print.
+        """
+        print(*args, file=sys.stderr, **kwargs)
+        sys.exit(1)
+
+    def inference(self):
+        """
+        Inference the picture or video, pictures and videos are always predicted.
+        """
+        try:
+            self.model, names = self.load_model()
+            self.names = {class_id: name for class_id, name in enumerate(names)}
+            if self.half:
+                self.model.half()
+            suffix = self.media_path.suffix.lower()
+            if suffix in PICTURE_SUFFIXES:
+                self.inference_picture()
+            elif suffix in VIDEO_SUFFIXES:
+                self.inference_video()
+            else:
+                self.panic(f"Unsupported file type {suffix}.")
+            self.save_detection()
+        except Exception as e:
+            self.panic(f"An unexpected error occurred: {e}")
+
+
+def argument_parser(description: str, repository: Path) -> argparse.ArgumentParser:
+    """
+    Create the argument parser shared by the YOLOv4 and YOLOv7 scripts.
+
+    :param description: Description of the script.
+    :param repository: Default path of the cloned repository.
+    :return: Argument parser.
+    """
+    parser = argparse.ArgumentParser(description=description,
+                                     usage='%(prog)s mode model media save imgsz conf batch verbose',
+                                     formatter_class=argparse.RawTextHelpFormatter)
+    parser.add_argument('mode', type=str, choices=['predict', 'track'],
+                        help="Mode of operation, tracking is not supported and falls back to predict")
+    parser.add_argument('model_path', type=Path, help='Path to the model')
+    parser.add_argument('media_path', type=Path, help='Path to the input picture or video')
+    parser.add_argument('save_path', type=Path, help='Path to save the output')
+    parser.add_argument('imgsz', type=int, help='Image size for processing')
+    parser.add_argument('conf', type=float, help='Confidence threshold')
+    parser.add_argument('batch', type=int, help='Batch size')
+    parser.add_argument('--repository', type=Path, default=repository, help='Path to the cloned repository')
+    parser.add_argument('--verbose', action='store_true', help='Enable verbose logging')
+    return parser
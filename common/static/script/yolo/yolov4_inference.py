@@ -0,0 +1,52 @@
+import sys
+from pathlib import Path
+
+import torch
+
+from yolo_inference import YoloInference, argument_parser
+
+
+class YOLOv4Inference(YoloInference):
+    def __init__(self, repository: Path, model_path: Path, media_path: Path, save_path: Path, cfg: Path, names: Path,
+                 **kwargs):
+        super().__init__(repository, model_path, media_path, save_path, **kwargs)
+        self.cfg = cfg or repository / 'cfg' / 'yolov4.cfg'
+        self.names_path = names or repository / 'data' / 'coco.names'
+
+    def load_model(self):
+        """
+        Build the Darknet model from its cfg and load either a PyTorch checkpoint or Darknet weights.
+
+        :return: Tuple of the model in evaluation mode and a list of class names.
+        """
+        from models.models import Darknet, load_darknet_weights
+        model = Darknet(str(self.cfg), self.imgsz)
+        if self.model_path.suffix.lower() == '.weights':
+            load_darknet_weights(model, str(self.model_path))
+        else:
+            checkpoint = torch.load(str(self.model_path), map_location=self.device)
+            model.load_state_dict(checkpoint['model'] if 'model' in checkpoint else checkpoint)
+        model.to(self.device).eval()
+        with open(self.names_path) as file:
+            names = [line.strip() for line in file if line.strip()]
+        return model, names
+
+    def non_max_suppression(self, prediction: torch.Tensor) -> list:
+        from utils.general import non_max_suppression
+        return non_max_suppression(prediction, conf_thres=self.conf, iou_thres=self.iou)
+
+
+if __name__ == "__main__":
+    try:
+        default_repository = Path(__file__).resolve().parent.parent / 'PyTorch_YOLOv4'
+        parser = argument_parser('YOLOv4 Inference Script', default_repository)
+        parser.add_argument('--cfg', type=Path, default=None, help='Path to the model cfg, defaults to yolov4.cfg')
+        parser.add_argument('--names', type=Path, default=None, help='Path to the class names, defaults to coco.names')
+        args = parser.parse_args()
+        other_args = {'imgsz': args.imgsz, 'conf': args.conf, 'batch': args.batch, 'verbose': args.verbose}
+        inference = YOLOv4Inference(args.repository, args.model_path, args.media_path, args.save_path,
+                                    args.cfg, args.names, **other_args)
+        inference.inference()
+    except Exception as e:
+        print(f"An unexpected error occurred: {e}", file=sys.stderr)
+        sys.exit(1)
@@ -0,0 +1,37 @@
+import sys
+from pathlib import Path
+
+import torch
+
+from yolo_inference import YoloInference, argument_parser
+
+
+class YOLOv7Inference(YoloInference):
+    def load_model(self):
+        """
+        Load a YOLOv7 checkpoint with the repository's loader.
+
+        :return: Tuple of the model in evaluation mode and a list of class names.
+        """
+        from models.experimental import attempt_load
+        model = attempt_load(str(self.model_path), map_location=self.device)
+        model.eval()
+        names = model.module.names if hasattr(model, 'module') else model.names
+        return model, list(names)
+
+    def non_max_suppression(self, prediction: torch.Tensor) -> list:
+        from utils.general import non_max_suppression
+        return non_max_suppression(prediction, conf_thres=self.conf, iou_thres=self.iou)
+
+
+if __name__ == "__main__":
+    try:
+        default_repository = Path(__file__).resolve().parent.parent / 'yolov7'
+        parser = argument_parser('YOLOv7 Inference Script', default_repository)
+        args = parser.parse_args()
+        other_args = {'imgsz': args.imgsz, 'conf': args.conf, 'batch': args.batch, 'verbose': args.verbose}
+        inference = YOLOv7Inference(args.repository, args.model_path, args.media_path, args.save_path, **other_args)
+        inference.inference()
+    except Exception as e:
+        print(f"An unexpected error occurred: {e}", file=sys.stderr)
+        sys.exit(1)
@@ -31,9 +31,16 @@ impl ReceiveThread {
                 packet = self.socket_rx.receive_packet() => {
                     match packet {
                         Ok(packet) => {
-                            let packet_type = PacketType::parse_packet_type(&packet.clone_id_byte());
+                            let packet_type = match PacketType::parse_packet_type(packet.as_id_byte()) {
+                                Ok(packet_type) => packet_type,
+                                Err(entry) => {
+                                    logging_warning!(self.agent_id, entry, "");
+                                    continue;
+                                }
+                            };
                             let result = match packet_type {
                                 PacketType::AgentInfoPacket => self.receiver_tx.agent_info_packet.send(packet),
+                                PacketType::AgentInfoRejectPacket => self.receiver_tx.agent_info_reject_packet.send(packet),
                                 PacketType::ControlAckPacket => self.receiver_tx.control_ack_packet.send(packet),
                                 PacketType::PerformancePacket => self.receiver_tx.performance_packet.send(packet),
                                 _ => {
@@ -9,6 +9,7 @@ use uuid::Uuid;
 
 pub struct ReceiverTX {
     pub agent_info_packet: UnboundedSender<BasePacket>,
+    pub agent_info_reject_packet: UnboundedSender<BasePacket>,
     pub control_ack_packet: UnboundedSender<BasePacket>,
     pub performance_packet: UnboundedSender<BasePacket>,
 }
@@ -17,18 +18,20 @@ pub struct ControlChannelReceiver {
     agent_id: Uuid,
     stop_signal_tx: Option<oneshot::Sender<()>>,
     pub agent_info_packet: UnboundedReceiver<BasePacket>,
+    pub agent_info_reject_packet: UnboundedReceiver<BasePacket>,
     pub control_ack_packet: UnboundedReceiver<BasePacket>,
     pub performance_packet: UnboundedReceiver<BasePacket>,
 }
 
 impl ControlChannelReceiver {
     pub fn new(agent_id: Uuid, socket_rx: ReadHalf) -> Self {
-        create_unbounded_channels!(3);
+        create_unbounded_channels!(4);
         let (stop_signal_tx, stop_signal_rx) = oneshot::channel();
         let receiver_tx = ReceiverTX {
             agent_info_packet: channel_0_tx,
-            control_ack_packet: channel_1_tx,
-            performance_packet: channel_2_tx,
+            agent_info_reject_packet: channel_1_tx,
+            control_ack_packet: channel_2_tx,
+            performance_packet: channel_3_tx,
         };
         let mut receive_thread = ReceiveThread::new(agent_id, socket_rx, receiver_tx, stop_signal_rx);
         tokio::spawn(async move {
@@ -38,13 +41,15 @@ impl ControlChannelReceiver {
             agent_id,
             stop_signal_tx: Some(stop_signal_tx),
             agent_info_packet: channel_0_rx,
-            control_ack_packet: channel_1_rx,
-            performance_packet: channel_2_rx,
+            agent_info_reject_packet: channel_1_rx,
+            control_ack_packet: channel_2_rx,
+            performance_packet: channel_3_rx,
         }
     }
 
     pub async fn disconnect(&mut self) {
         self.agent_info_packet.close();
+        self.agent_info_reject_packet.close();
         self.control_ack_packet.close();
         self.performance_packet.close();
         match self.stop_signal_tx.take() {
@@ -31,7 +31,13 @@ impl ReceiveThread {
                 packet = self.socket_rx.receive_packet() => {
                     match packet {
                         Ok(packet) => {
-                            let packet_type = PacketType::parse_packet_type(&packet.clone_id_byte());
+                            let packet_type = match PacketType::parse_packet_type(packet.as_id_byte()) {
+                                Ok(packet_type) => packet_type,
+                                Err(entry) => {
+                                    logging_warning!(self.agent_id, entry, "");
+                                    continue;
+                                }
+                            };
                             let result = match packet_type {
                                 PacketType::AliveAckPacket => self.receiver_tx.alive_ack_packet.send(packet),
                                 PacketType::FileBodyPacket => self.receiver_tx.file_body_packet.send(packet),
@@ -1,5 +1,6 @@
 pub mod agent_info_ack_packet;
 pub mod alive_packet;
+pub mod cancel_task_packet;
 pub mod control_packet;
 pub mod data_channel_port_packet;
 pub mod performance_ack_packet;
@@ -3,7 +3,7 @@ use crate::utils::config::Config;
 use crate::utils::logging::*;
 use std::net::SocketAddr;
 use std::time::Duration;
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, TcpStream};
 use tokio::time::sleep;
 
 pub struct AgentSocket {
@@ -28,12 +28,18 @@ impl AgentSocket {
         }
     }
 
-    pub async fn get_connection(&mut self) -> (SocketStream, SocketAddr) {
-        let (stream, address) = loop {
-            if let Ok(connection) = self.listener.accept().await {
-                break connection;
+    pub async fn get_connection(&mut self) -> (TcpStream, SocketAddr) {
+        loop {
+            match self.listener.accept().await {
+                Ok(connection) => break connection,
+                Err(err) => logging_error!(NetworkEntry::EstablishConnectionError(err)),
             }
-        };
-        (SocketStream::new(stream), address)
+        }
+    }
+
+    //Runs in the task spawned for each connection, a stalled client only holds up its own handshake.
+    pub async fn handshake(tcp_stream: TcpStream) -> Result<SocketStream, LogEntry> {
+        let config = Config::now().await;
+        config.tls.accept(tcp_stream, Duration::from_secs(config.control_channel_timeout)).await
     }
 }
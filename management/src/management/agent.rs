@@ -5,7 +5,9 @@ use crate::connection::channel::data_channel_sender::DataChannelSender;
 use crate::connection::channel::ControlChannel;
 use crate::connection::channel::DataChannel;
 use crate::connection::packet::agent_info_ack_packet::AgentInfoAckPacket;
+use crate::connection::packet::agent_info_reject_packet::AgentInfoRejectPacket;
 use crate::connection::packet::alive_packet::AlivePacket;
+use crate::connection::packet::cancel_task_packet::CancelTaskPacket;
 use crate::connection::packet::control_packet::ControlPacket;
 use crate::connection::packet::data_channel_port_packet::DataChannelPortPacket;
 use crate::connection::packet::file_body_packet::FileBodyPacket;
@@ -20,24 +22,30 @@ use crate::connection::packet::task_info_packet::TaskInfoPacket;
 use crate::connection::packet::Packet;
 use crate::connection::socket::socket_stream::SocketStream;
 use crate::management::agent_manager::AgentManager;
+use crate::management::resource_estimator::ResourceEstimator;
 use crate::management::task_manager::TaskManager;
 use crate::management::utils::agent_information::AgentInformation;
+use crate::management::utils::agent_registration::AgentRegistration;
 use crate::management::utils::agent_state::AgentState;
+use crate::management::utils::backend_capability::BackendCapability;
+use crate::management::utils::capability::{Capability, Negotiation, TRANSFER_WINDOW_SIZE};
 use crate::management::utils::file_header::FileHeader;
+use crate::management::utils::file_reader::FileReader;
 use crate::management::utils::file_transfer_result::FileTransferResult;
+use crate::management::utils::file_writer::FileWriter;
+use crate::management::utils::inference_argument::ModelType;
 use crate::management::utils::inference_task::InferenceTask;
 use crate::management::utils::performance::Performance;
 use crate::management::utils::task_result::TaskResult;
+use crate::management::utils::throughput::Throughput;
 use crate::utils::clear_unbounded_channel;
 use crate::utils::config::Config;
 use crate::utils::logging::*;
 use crate::utils::port_pool::PortPool;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashSet, VecDeque};
 use std::mem;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::fs::File;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpListener;
 use tokio::select;
 use tokio::sync::RwLock;
@@ -47,11 +55,16 @@ use uuid::Uuid;
 pub struct Agent {
     uuid: Uuid,
     state: AgentState,
+    token: String,
     information: AgentInformation,
+    negotiation: Negotiation,
+    backends: Vec<BackendCapability>, //reported backends of the negotiated model types
     idle_unused: Performance,
     realtime_usage: Performance,
-    previous_task_uuid: Option<Uuid>,
-    inference_task: VecDeque<InferenceTask>,
+    cached_models: HashSet<String>,
+    concurrency: usize,
+    in_flight_task: Vec<InferenceTask>,
+    throughput: Throughput,
     control_channel_sender: ControlChannelSender,
     control_channel_receiver: ControlChannelReceiver,
     data_channel_sender: Option<DataChannelSender>,
@@ -61,38 +74,70 @@ pub struct Agent {
 impl Agent {
     pub async fn new(uuid: Uuid, socket_stream: SocketStream) -> Result<Self, LogEntry> {
         let config = Config::now().await;
-        let mut agent_information: Option<AgentInformation> = None;
+        let mut agent_registration: Option<(AgentRegistration, Negotiation)> = None;
+        let capability = Self::capability();
+        let capability_data = serde_json::to_vec(&capability)
+            .map_err(|err| error_entry!(IOEntry::SerdeSerializeError(err)))?;
         let (mut control_channel_sender, mut control_channel_receiver) = ControlChannel::new(uuid, socket_stream);
         let timer = Instant::now();
         let timeout_duration = Duration::from_secs(config.control_channel_timeout);
         while timer.elapsed() <= timeout_duration {
             select! {
                 biased;
-                packet = control_channel_receiver.agent_info_packet.recv(), if agent_information.is_none() => {
+                packet = control_channel_receiver.agent_info_packet.recv(), if agent_registration.is_none() => {
                     let packet = packet
                         .ok_or(information_entry!(NetworkEntry::ChannelClosed))?;
-                    let information = serde_json::from_slice::<AgentInformation>(packet.as_data_byte())
+                    let registration = serde_json::from_slice::<AgentRegistration>(packet.as_data_byte())
                         .map_err(|err| error_entry!(IOEntry::SerdeDeserializeError(err)))?;
-                    agent_information = Some(information);
-                    control_channel_sender.send(AgentInfoAckPacket::new()).await;
+                    let negotiation = config.agent_authentication.authorize(&registration.token)
+                        .map_err(NetworkEntry::AgentRejected)
+                        .and_then(|_| capability.negotiate(&registration.capability).map_err(NetworkEntry::IncompatiblePeer));
+                    match negotiation {
+                        Ok(negotiation) => {
+                            agent_registration = Some((registration, negotiation));
+                            control_channel_sender.send(AgentInfoAckPacket::new(capability_data.clone())).await;
+                        },
+                        Err(entry) => {
+                            control_channel_sender.send(AgentInfoRejectPacket::new(entry.to_string().into_bytes())).await;
+                            control_channel_sender.disconnect().await;
+                            control_channel_receiver.disconnect().await;
+                            Err(warning_entry!(entry))?;
+                        },
+                    }
+                },
+                packet = control_channel_receiver.agent_info_reject_packet.recv() => {
+                    let packet = packet
+                        .ok_or(information_entry!(NetworkEntry::ChannelClosed))?;
+                    control_channel_sender.disconnect().await;
+                    control_channel_receiver.disconnect().await;
+                    Err(warning_entry!(NetworkEntry::RejectedByAgent(packet.data_to_string())))?;
                 },
                 packet = control_channel_receiver.performance_packet.recv() => {
                     let packet = packet
                         .ok_or(information_entry!(NetworkEntry::ChannelClosed))?;
                     let realtime_usage = serde_json::from_slice::<Performance>(packet.as_data_byte())
                         .map_err(|err| error_entry!(IOEntry::SerdeDeserializeError(err)))?;
-                    let information = agent_information
+                    let (AgentRegistration { token, information, cached_models, concurrency, .. }, negotiation) = agent_registration
                         .ok_or(error_entry!(MiscEntry::WrongDeliverOrder))?;
                     control_channel_sender.send(PerformanceAckPacket::new()).await;
                     let residual_usage = Performance::calc_residual_usage(&information, &realtime_usage);
+                    let backends = information.backends.iter()
+                        .filter(|backend| negotiation.model_types.contains(&backend.model_type))
+                        .cloned()
+                        .collect();
                     let agent = Self {
                         uuid,
                         state: AgentState::CreateDataChannel,
+                        token,
                         information,
+                        negotiation,
+                        backends,
                         idle_unused: residual_usage,
                         realtime_usage,
-                        previous_task_uuid: None,
-                        inference_task: VecDeque::new(),
+                        cached_models: cached_models.into_iter().collect(),
+                        concurrency: concurrency.max(1),
+                        in_flight_task: Vec::new(),
+                        throughput: Throughput::default(),
                         control_channel_sender,
                         control_channel_receiver,
                         data_channel_sender: None,
@@ -106,8 +151,32 @@ impl Agent {
         Err(information_entry!(NetworkEntry::ControlChannelTimeout))
     }
 
-    pub async fn add_task(agent: Arc<RwLock<Agent>>, inference_task: InferenceTask) {
-        agent.write().await.inference_task.push_back(inference_task);
+    fn capability() -> Capability {
+        Capability::new(vec![ModelType::Ultralytics, ModelType::YOLOv4, ModelType::YOLOv7, ModelType::Onnx])
+    }
+
+    pub async fn cancel_task(agent: Arc<RwLock<Agent>>, task_uuid: Uuid) {
+        let mut agent = agent.write().await;
+        let uuid = agent.uuid;
+        if !agent.in_flight_task.iter().any(|inference_task| inference_task.task_uuid == task_uuid) {
+            return;
+        }
+        match serde_json::to_vec(&task_uuid) {
+            Ok(task_uuid_data) => match agent.data_channel_sender.as_mut() {
+                Some(data_channel_sender) => data_channel_sender.send(CancelTaskPacket::new(task_uuid_data)).await,
+                None => logging_warning!(uuid, NetworkEntry::DataChannelNotReady, ""),
+            },
+            Err(err) => logging_error!(uuid, IOEntry::SerdeSerializeError(err), ""),
+        }
+    }
+
+    pub async fn authorize(agent: Arc<RwLock<Agent>>) {
+        let config = Config::now().await;
+        let mut agent = agent.write().await;
+        if let Err(reason) = config.agent_authentication.authorize(&agent.token) {
+            logging_warning!(agent.uuid, NetworkEntry::AgentRejected(reason), "");
+            agent.state = AgentState::Terminate;
+        }
     }
 
     pub async fn run(agent: Arc<RwLock<Agent>>) {
@@ -168,6 +237,7 @@ impl Agent {
             let state = agent.read().await.state;
             match state {
                 AgentState::CreateDataChannel => {
+                    Self::fail_in_flight_task(&agent, warning_entry!(NetworkEntry::DataChannelNotReady)).await;
                     Self::send_state(&agent, AgentState::CreateDataChannel).await;
                     Self::create_data_channel(&agent).await;
                 }
@@ -177,14 +247,33 @@ impl Agent {
                     return;
                 }
                 _ => {
-                    let inference_task = agent.write().await.inference_task.pop_front();
-                    if let Some(mut inference_task) = inference_task {
+                    if let Some(mut inference_task) = Self::next_task(&agent).await {
                         let state = AgentState::ProcessTask;
                         agent.write().await.state = state;
                         Self::send_state(&agent, state).await;
-                        let result = Self::process_task(&agent, &mut inference_task).await;
-                        inference_task.error = result;
-                        TaskManager::submit_inference_task(inference_task).await;
+                        agent.write().await.throughput.start(inference_task.subtask_uuid);
+                        match Self::process_task(&agent, &inference_task).await {
+                            Ok(()) => agent.write().await.in_flight_task.push(inference_task),
+                            Err(err) => {
+                                let (uuid, terminating) = {
+                                    let mut agent = agent.write().await;
+                                    agent.throughput.forget(inference_task.subtask_uuid);
+                                    (agent.uuid, agent.state == AgentState::Terminate)
+                                };
+                                //A disconnect is not the subtask's fault, so it is handed back without using up an attempt.
+                                if terminating {
+                                    TaskManager::redistribute_task(VecDeque::from([inference_task])).await;
+                                } else {
+                                    inference_task.record_failure(uuid, err);
+                                    TaskManager::retry_inference_task(inference_task).await;
+                                }
+                            }
+                        }
+                    } else if !agent.read().await.in_flight_task.is_empty() {
+                        let state = AgentState::CollectResult;
+                        agent.write().await.state = state;
+                        Self::send_state(&agent, state).await;
+                        Self::collect_result(&agent).await;
                     } else {
                         let state = AgentState::Idle(config.agent_idle_duration);
                         agent.write().await.state = state;
@@ -234,45 +323,109 @@ impl Agent {
         agent.write().await.state = AgentState::Terminate;
     }
 
-    async fn process_task(agent: &Arc<RwLock<Agent>>, inference_task: &mut InferenceTask) -> Result<(), String> {
+    async fn next_task(agent: &Arc<RwLock<Agent>>) -> Option<InferenceTask> {
+        let config = Config::now().await;
+        {
+            let agent = agent.read().await;
+            if agent.in_flight_task.len() >= agent.capacity() + config.agent_prefetch_depth {
+                return None;
+            }
+        }
+        TaskManager::pull_task(agent).await
+    }
+
+    async fn process_task(agent: &Arc<RwLock<Agent>>, inference_task: &InferenceTask) -> Result<(), String> {
         let uuid = agent.read().await.uuid;
-        if let Err(entry) = Self::transfer_task(&agent, inference_task).await {
+        if let Err(entry) = Self::transfer_task(agent, inference_task).await {
             logging_entry!(uuid, entry.clone());
             return Err(entry.message);
         }
-        if let Err(entry) = Self::waiting_complete(&agent).await {
-            logging_entry!(uuid, entry.clone());
-            return Err(entry.message);
+        Ok(())
+    }
+
+    async fn collect_result(agent: &Arc<RwLock<Agent>>) {
+        let uuid = agent.read().await.uuid;
+        let task_result = match Self::waiting_complete(agent).await {
+            Ok(task_result) => task_result,
+            Err(entry) => {
+                logging_entry!(uuid, entry.clone());
+                Self::fail_in_flight_task(agent, entry).await;
+                return;
+            }
+        };
+        let inference_task = {
+            let mut agent = agent.write().await;
+            agent.in_flight_task.iter()
+                .position(|inference_task| inference_task.subtask_uuid == task_result.subtask_uuid)
+                .map(|index| agent.in_flight_task.remove(index))
+        };
+        let Some(mut inference_task) = inference_task else {
+            logging_warning!(uuid, MiscEntry::WrongDeliverOrder, "");
+            agent.write().await.state = AgentState::CreateDataChannel;
+            return;
+        };
+        let result = match task_result.status {
+            Ok(()) => {
+                agent.write().await.throughput.complete(inference_task.subtask_uuid, inference_task.media_size);
+                if let Some(resource_usage) = task_result.resource_usage {
+                    ResourceEstimator::record(&inference_task, inference_task.media_size, resource_usage).await;
+                }
+                Self::receive_result(agent, &inference_task).await
+            },
+            Err(err) => {
+                agent.write().await.throughput.forget(inference_task.subtask_uuid);
+                Err(error_entry!(TaskEntry::AgentProcessingError(err), task_result.stderr))
+            },
+        };
+        match result {
+            Ok(()) => TaskManager::submit_inference_task(inference_task).await,
+            Err(entry) => {
+                logging_entry!(uuid, entry.clone());
+                inference_task.record_failure(uuid, entry.message);
+                TaskManager::retry_inference_task(inference_task).await;
+            }
         }
-        if let Err(entry) = Self::receive_result(&agent, inference_task).await {
-            logging_entry!(uuid, entry.clone());
-            return Err(entry.message);
+    }
+
+    async fn fail_in_flight_task(agent: &Arc<RwLock<Agent>>, entry: LogEntry) {
+        let (uuid, in_flight_task) = {
+            let mut agent = agent.write().await;
+            //Tasks of a terminating agent are handed back untouched when it is removed.
+            if agent.state == AgentState::Terminate {
+                return;
+            }
+            let in_flight_task = mem::take(&mut agent.in_flight_task);
+            for inference_task in &in_flight_task {
+                agent.throughput.forget(inference_task.subtask_uuid);
+            }
+            (agent.uuid, in_flight_task)
+        };
+        for mut inference_task in in_flight_task {
+            inference_task.record_failure(uuid, entry.message.clone());
+            TaskManager::retry_inference_task(inference_task).await;
         }
-        Ok(())
     }
 
     async fn transfer_task(agent: &Arc<RwLock<Agent>>, inference_task: &InferenceTask) -> Result<(), LogEntry> {
         if agent.write().await.data_channel_sender.is_some() {
-            let need_transfer_model = if let Some(last_task_uuid) = &agent.read().await.previous_task_uuid {
-                inference_task.task_uuid != *last_task_uuid
-            } else {
-                true
-            };
-            Agent::transfer_task_info(agent, &inference_task).await?;
+            Agent::transfer_task_info(agent, inference_task).await?;
+            let model_hash = &inference_task.model_hash;
+            let need_transfer_model = model_hash.is_empty() || !agent.read().await.cached_models.contains(model_hash);
             if need_transfer_model {
                 Agent::transfer_file(agent, &inference_task.model_file_name, &inference_task.model_file_path).await?;
-                agent.write().await.previous_task_uuid = Some(inference_task.task_uuid);
             }
             Agent::transfer_file(agent, &inference_task.media_file_name, &inference_task.media_file_path).await?;
         } else {
             agent.write().await.state = AgentState::CreateDataChannel;
+            Err(warning_entry!(NetworkEntry::DataChannelNotReady))?;
         }
         Ok(())
     }
 
     async fn transfer_task_info(agent: &Arc<RwLock<Agent>>, inference_task: &InferenceTask) -> Result<(), LogEntry> {
         let config = Config::now().await;
-        let task_info = inference_task.as_task_info();
+        let estimated_vram = TaskManager::estimated_vram_usage(inference_task).await;
+        let task_info = inference_task.as_task_info(estimated_vram);
         let task_info_data = serde_json::to_vec(&task_info)
             .map_err(|err| error_entry!(IOEntry::SerdeSerializeError(err)))?;
         let timer = Instant::now();
@@ -300,7 +453,10 @@ impl Agent {
                     select! {
                         packet = data_channel_receiver.task_info_ack_packet.recv() => {
                             clear_unbounded_channel(&mut data_channel_receiver.task_info_ack_packet).await;
-                            if packet.is_some() {
+                            if let Some(packet) = packet {
+                                let cached_models = serde_json::from_slice::<Vec<String>>(packet.as_data_byte())
+                                    .map_err(|err| error_entry!(IOEntry::SerdeDeserializeError(err)))?;
+                                agent.cached_models = cached_models.into_iter().collect();
                                 return Ok(())
                             } else {
                                 agent.state = AgentState::CreateDataChannel;
@@ -320,37 +476,16 @@ impl Agent {
     }
 
     async fn transfer_file(agent: &Arc<RwLock<Agent>>, file_name: &String, file_path: &PathBuf) -> Result<(), LogEntry> {
-        let file_body = Self::read_file(agent, file_path).await?;
-        Self::transfer_file_header(agent, file_name, file_body.len()).await?;
-        Self::transfer_file_body(agent, file_body).await
-    }
-
-    async fn read_file(agent: &Arc<RwLock<Agent>>, file_path: &PathBuf) -> Result<Vec<Vec<u8>>, LogEntry> {
-        let mut sequence_number = 0_usize;
-        let mut buffer = vec![0; 1_048_576];
-        let mut packets = Vec::new();
-        let mut file = File::open(file_path.clone()).await
-            .map_err(|err| error_entry!(IOEntry::ReadFileError(file_path.display(), err)))?;
-        loop {
-            if agent.read().await.state == AgentState::Terminate {
-                Err(information_entry!(SystemEntry::Cancel))?
-            }
-            let bytes_read = file.read(&mut buffer).await
-                .map_err(|err| error_entry!(IOEntry::ReadFileError(file_path.display(), err)))?;
-            if bytes_read == 0 {
-                return Ok(packets);
-            }
-            let mut data = sequence_number.to_be_bytes().to_vec();
-            data.extend_from_slice(&buffer[..bytes_read]);
-            packets.push(data);
-            sequence_number += 1;
-        }
+        let chunk_size = agent.read().await.negotiation.chunk_size;
+        let mut file_reader = FileReader::open(file_path, chunk_size).await?;
+        let file_header = file_reader.file_header(file_name.clone(), TRANSFER_WINDOW_SIZE);
+        Self::transfer_file_header(agent, &file_header).await?;
+        Self::transfer_file_body(agent, &mut file_reader, &file_header).await
     }
 
-    async fn transfer_file_header(agent: &Arc<RwLock<Agent>>, file_name: &String, packet_count: usize) -> Result<(), LogEntry> {
+    async fn transfer_file_header(agent: &Arc<RwLock<Agent>>, file_header: &FileHeader) -> Result<(), LogEntry> {
         let config = Config::now().await;
-        let file_header = FileHeader::new(file_name.clone(), packet_count);
-        let file_header_data = serde_json::to_vec(&file_header)
+        let file_header_data = serde_json::to_vec(file_header)
             .map_err(|err| error_entry!(IOEntry::SerdeSerializeError(err)))?;
         let timer = Instant::now();
         let mut polling_times = 0_u32;
@@ -393,9 +528,19 @@ impl Agent {
         Err(information_entry!(SystemEntry::Cancel))
     }
 
-    async fn transfer_file_body(agent: &Arc<RwLock<Agent>>, file_body: Vec<Vec<u8>>) -> Result<(), LogEntry> {
+    async fn transfer_file_body(agent: &Arc<RwLock<Agent>>, file_reader: &mut FileReader, file_header: &FileHeader) -> Result<(), LogEntry> {
+        let mut window_start = 0_usize;
+        while window_start < file_header.packet_count {
+            let window_end = file_header.window_end(window_start);
+            file_reader.read_window(window_start, window_end).await?;
+            Self::transfer_window(agent, file_reader, (window_start..window_end).collect()).await?;
+            window_start = window_end;
+        }
+        Ok(())
+    }
+
+    async fn transfer_window(agent: &Arc<RwLock<Agent>>, file_reader: &FileReader, mut require_send: Vec<usize>) -> Result<(), LogEntry> {
         let config = Config::now().await;
-        let mut require_send: Vec<usize> = (0..file_body.len()).collect();
         let mut timer = Instant::now();
         let timeout_duration = Duration::from_secs(config.file_transfer_timeout);
         while agent.read().await.state != AgentState::Terminate {
@@ -404,7 +549,7 @@ impl Agent {
                 Err(information_entry!(NetworkEntry::DataChannelTimeout))?;
             }
             for chunk in &require_send {
-                if let Some(data) = file_body.get(*chunk) {
+                if let Some(data) = file_reader.chunk(*chunk) {
                     if let Some(data_channel_sender) = agent.write().await.data_channel_sender.as_mut() {
                         data_channel_sender.send(FileBodyPacket::new(data.clone())).await;
                     } else {
@@ -417,7 +562,7 @@ impl Agent {
             }
             if !require_send.is_empty() {
                 if let Some(data_channel_sender) = agent.write().await.data_channel_sender.as_mut() {
-                    data_channel_sender.send(FileTransferEndPacket::new()).await;
+                    data_channel_sender.send(FileTransferEndPacket::new(file_reader.trailer())).await;
                 } else {
                     agent.write().await.state = AgentState::CreateDataChannel;
                     Err(warning_entry!(NetworkEntry::DataChannelNotReady))?
@@ -456,7 +601,7 @@ impl Agent {
         Err(information_entry!(SystemEntry::Cancel))?
     }
 
-    async fn waiting_complete(agent: &Arc<RwLock<Agent>>) -> Result<(), LogEntry> {
+    async fn waiting_complete(agent: &Arc<RwLock<Agent>>) -> Result<TaskResult, LogEntry> {
         if let Some(data_channel_receiver) = agent.write().await.data_channel_receiver.as_mut() {
             clear_unbounded_channel(&mut data_channel_receiver.task_result_packet).await;
         }
@@ -498,7 +643,7 @@ impl Agent {
                     packet = data_channel_receiver.task_result_packet.recv() => {
                         if let Some(packet) = &packet {
                             match serde_json::from_slice::<TaskResult>(packet.as_data_byte()) {
-                                Ok(task_result) => break task_result.into(),
+                                Ok(task_result) => break task_result,
                                 Err(err) => Err(error_entry!(IOEntry::SerdeDeserializeError(err)))?,
                             }
                         } else {
@@ -515,12 +660,11 @@ impl Agent {
         };
         if let Some(data_channel_sender) = agent.write().await.data_channel_sender.as_mut() {
             data_channel_sender.send(TaskResultAckPacket::new()).await;
-            task_result.map_err(|err| error_entry!(TaskEntry::AgentProcessingError(err)))?;
         } else {
             agent.write().await.state = AgentState::CreateDataChannel;
             Err(warning_entry!(NetworkEntry::DataChannelNotReady))?;
         }
-        Ok(())
+        Ok(task_result)
     }
 
     async fn receive_result(agent: &Arc<RwLock<Agent>>, inference_task: &InferenceTask) -> Result<(), LogEntry> {
@@ -529,9 +673,13 @@ impl Agent {
         let save_folder = PathBuf::from(format!("./PostProcess/{}", uuid));
         #[cfg(target_os = "windows")]
         let save_folder = PathBuf::from(format!(".\\PostProcess\\{}", uuid));
-        let file_header = Self::receive_file_header(agent.clone()).await?;
-        let file_body = Self::receive_file_body(agent.clone(), &file_header).await?;
-        Self::create_file(file_header, file_body, save_folder).await?;
+        let chunk_size = agent.read().await.negotiation.chunk_size;
+        for _ in 0..2 {
+            let file_header = Self::receive_file_header(agent.clone()).await?;
+            let mut file_writer = FileWriter::create(&save_folder, file_header, chunk_size).await?;
+            Self::receive_file_body(agent.clone(), &mut file_writer).await?;
+            file_writer.finish().await?;
+        }
         Ok(())
     }
 
@@ -578,16 +726,15 @@ impl Agent {
         Ok(file_header)
     }
 
-    async fn receive_file_body(agent: Arc<RwLock<Agent>>, file_header: &FileHeader) -> Result<Vec<Vec<u8>>, LogEntry> {
+    async fn receive_file_body(agent: Arc<RwLock<Agent>>, file_writer: &mut FileWriter) -> Result<(), LogEntry> {
+        let uuid = agent.read().await.uuid;
         if let Some(data_channel_receiver) = agent.write().await.data_channel_receiver.as_mut() {
             clear_unbounded_channel(&mut data_channel_receiver.file_body_packet).await;
         }
         let config = Config::now().await;
-        let mut file_block: HashMap<usize, Vec<u8>> = HashMap::new();
-        let mut missing_blocks = Vec::new();
         let mut timer = Instant::now();
         let timeout_duration = Duration::from_secs(config.data_channel_timeout);
-        loop {
+        while !file_writer.is_complete() {
             if agent.read().await.state == AgentState::Terminate {
                 Err(information_entry!(SystemEntry::Cancel))?;
             }
@@ -595,137 +742,99 @@ impl Agent {
                 agent.write().await.state = AgentState::CreateDataChannel;
                 Err(information_entry!(NetworkEntry::DataChannelTimeout))?;
             }
-            if let Some(data_channel_receiver) = agent.write().await.data_channel_receiver.as_mut() {
+            let missing_chunks = if let Some(data_channel_receiver) = agent.write().await.data_channel_receiver.as_mut() {
                 select! {
                     biased;
                     packet = data_channel_receiver.file_body_packet.recv() => {
                         if let Some(packet) = packet {
-                            clear_unbounded_channel(&mut data_channel_receiver.file_body_packet).await;
                             timer = Instant::now();
-                            let (sequence_bytes, file_body) = packet.data.split_at(size_of::<usize>());
-                            let sequence_bytes = sequence_bytes.try_into()
-                                .map_err(|_| error_entry!(MiscEntry::InvalidPacket))?;
-                            let sequence_number = usize::from_be_bytes(sequence_bytes);
-                            file_block.insert(sequence_number, Vec::from(file_body));
+                            if !file_writer.write_chunk(packet.as_data_byte()).await? {
+                                logging_warning!(uuid, MiscEntry::ChunkChecksumMismatch, "");
+                            }
                             continue;
                         } else {
                             agent.write().await.state = AgentState::CreateDataChannel;
-                            Err(information_entry!(NetworkEntry::ChannelClosed))?;
+                            Err(information_entry!(NetworkEntry::ChannelClosed))?
                         }
                     }
                     packet = data_channel_receiver.file_transfer_end_packet.recv() => {
                         clear_unbounded_channel(&mut data_channel_receiver.file_transfer_end_packet).await;
-                        if packet.is_some() {
-                            for sequence_number in 0..file_header.packet_count {
-                                if !file_block.contains_key(&sequence_number) {
-                                    missing_blocks.push(sequence_number);
-                                }
-                            }
+                        if let Some(packet) = packet {
                             timer = Instant::now();
+                            file_writer.end_window(packet.as_data_byte())
                         } else {
                             agent.write().await.state = AgentState::CreateDataChannel;
-                            Err(information_entry!(NetworkEntry::ChannelClosed))?;
+                            Err(information_entry!(NetworkEntry::ChannelClosed))?
                         }
                     }
                     _ = sleep(Duration::from_millis(config.internal_timestamp)) => continue,
                 }
             } else {
                 agent.write().await.state = AgentState::CreateDataChannel;
-                Err(warning_entry!(NetworkEntry::DataChannelNotReady))?;
-            }
+                Err(warning_entry!(NetworkEntry::DataChannelNotReady))?
+            };
             if let Some(data_channel_sender) = agent.write().await.data_channel_sender.as_mut() {
-                if missing_blocks.len() != 0_usize {
-                    let missing_blocks = mem::take(&mut missing_blocks);
-                    let result = FileTransferResult::new(Some(missing_blocks));
-                    let result_data = serde_json::to_vec(&result)
-                        .map_err(|err| error_entry!(IOEntry::SerdeDeserializeError(err)))?;
-                    data_channel_sender.send(FileTransferResultPacket::new(result_data)).await;
-                } else {
-                    let result = FileTransferResult::new(None);
-                    let result_data = serde_json::to_vec(&result)
-                        .map_err(|err| error_entry!(IOEntry::SerdeSerializeError(err)))?;
-                    data_channel_sender.send(FileTransferResultPacket::new(result_data)).await;
-                    let mut sorted_blocks: Vec<Vec<u8>> = Vec::with_capacity(file_header.packet_count);
-                    for index in 0..file_header.packet_count {
-                        if let Some(block) = file_block.remove(&index) {
-                            sorted_blocks.push(block);
-                        } else {
-                            Err(error_entry!(MiscEntry::MissingFileBlockError))?
-                        }
-                    }
-                    return Ok(sorted_blocks);
-                };
+                let result = FileTransferResult::new(missing_chunks);
+                let result_data = serde_json::to_vec(&result)
+                    .map_err(|err| error_entry!(IOEntry::SerdeSerializeError(err)))?;
+                data_channel_sender.send(FileTransferResultPacket::new(result_data)).await;
             } else {
                 agent.write().await.state = AgentState::CreateDataChannel;
                 Err(warning_entry!(NetworkEntry::DataChannelNotReady))?;
             }
         }
-    }
-
-    async fn create_file(file_header: FileHeader, file_body: Vec<Vec<u8>>, saved_folder: PathBuf) -> Result<(), LogEntry> {
-        let saved_path = saved_folder.join(file_header.file_name);
-        let mut file = File::create(&saved_path).await
-            .map_err(|err| error_entry!(IOEntry::CreateFileError(saved_path.display(), err)))?;
-        for chunk in file_body {
-            file.write_all(&chunk).await
-                .map_err(|err| error_entry!(IOEntry::CreateFileError(saved_path.display(), err)))?;
-        }
         Ok(())
     }
 
     async fn idle(agent: &Arc<RwLock<Agent>>) {
-        if let Some(inference_task) = TaskManager::steal_task(agent.clone()).await {
-            Agent::add_task(agent.clone(), inference_task).await;
-        } else {
-            let uuid = agent.read().await.uuid;
-            let config = Config::now().await;
-            {
-                let mut agent = agent.write().await;
-                agent.idle_unused = Performance::calc_residual_usage(&agent.information, &agent.realtime_usage);
-            }
-            let timer = Instant::now();
-            let mut polling_times = 0_u32;
-            let polling_interval = Duration::from_millis(config.polling_interval);
-            let idle_duration = Duration::from_secs(config.agent_idle_duration);
-            let mut timeout_timer = Instant::now();
-            let timeout_duration = Duration::from_secs(config.control_channel_timeout);
-            while agent.read().await.state != AgentState::Terminate && timer.elapsed() <= idle_duration {
-                if timeout_timer.elapsed() > timeout_duration {
-                    agent.write().await.state = AgentState::CreateDataChannel;
-                    logging_information!(uuid, NetworkEntry::DataChannelTimeout, "");
-                    return;
-                }
-                if timer.elapsed() > polling_times * polling_interval {
-                    if let Some(data_channel_sender) = agent.write().await.data_channel_sender.as_mut() {
-                        data_channel_sender.send(AlivePacket::new()).await
-                    } else {
-                        agent.write().await.state = AgentState::CreateDataChannel;
-                        logging_warning!(uuid, NetworkEntry::DataChannelNotReady, "");
-                        return;
-                    }
-                    polling_times += 1;
-                }
-                let mut agent = agent.write().await;
-                if let Some(data_channel_receiver) = agent.data_channel_receiver.as_mut() {
-                    select! {
-                        biased;
-                        packet = data_channel_receiver.alive_ack_packet.recv() => {
-                            if packet.is_some() {
-                                clear_unbounded_channel(&mut data_channel_receiver.alive_ack_packet).await;
-                                timeout_timer = Instant::now();
-                            } else {
-                                agent.state = AgentState::CreateDataChannel;
-                                logging_information!(uuid, NetworkEntry::ChannelClosed, "");
-                                return;
-                            }
-                        },
-                        _ = sleep(Duration::from_millis(config.internal_timestamp)) => continue,
-                    }
+        let uuid = agent.read().await.uuid;
+        let config = Config::now().await;
+        {
+            let mut agent = agent.write().await;
+            agent.idle_unused = Performance::calc_residual_usage(&agent.information, &agent.realtime_usage);
+        }
+        let timer = Instant::now();
+        let mut polling_times = 0_u32;
+        let polling_interval = Duration::from_millis(config.polling_interval);
+        let idle_duration = Duration::from_secs(config.agent_idle_duration);
+        let mut timeout_timer = Instant::now();
+        let timeout_duration = Duration::from_secs(config.control_channel_timeout);
+        while agent.read().await.state != AgentState::Terminate && timer.elapsed() <= idle_duration {
+            if timeout_timer.elapsed() > timeout_duration {
+                agent.write().await.state = AgentState::CreateDataChannel;
+                logging_information!(uuid, NetworkEntry::DataChannelTimeout, "");
+                return;
+            }
+            if timer.elapsed() > polling_times * polling_interval {
+                if let Some(data_channel_sender) = agent.write().await.data_channel_sender.as_mut() {
+                    data_channel_sender.send(AlivePacket::new()).await
                 } else {
-                    agent.state = AgentState::CreateDataChannel;
+                    agent.write().await.state = AgentState::CreateDataChannel;
                     logging_warning!(uuid, NetworkEntry::DataChannelNotReady, "");
                     return;
                 }
+                polling_times += 1;
+            }
+            let mut agent = agent.write().await;
+            if let Some(data_channel_receiver) = agent.data_channel_receiver.as_mut() {
+                select! {
+                    biased;
+                    packet = data_channel_receiver.alive_ack_packet.recv() => {
+                        if packet.is_some() {
+                            clear_unbounded_channel(&mut data_channel_receiver.alive_ack_packet).await;
+                            timeout_timer = Instant::now();
+                        } else {
+                            agent.state = AgentState::CreateDataChannel;
+                            logging_information!(uuid, NetworkEntry::ChannelClosed, "");
+                            return;
+                        }
+                    },
+                    _ = sleep(Duration::from_millis(config.internal_timestamp)) => continue,
+                }
+            } else {
+                agent.state = AgentState::CreateDataChannel;
+                logging_warning!(uuid, NetworkEntry::DataChannelNotReady, "");
+                return;
             }
         }
     }
@@ -793,7 +902,13 @@ impl Agent {
                 _ = sleep(Duration::from_millis(config.internal_timestamp)) => continue
             }
         };
-        let socket_stream = SocketStream::new(tcp_stream);
+        let socket_stream = match config.tls.accept(tcp_stream, timeout_duration).await {
+            Ok(socket_stream) => socket_stream,
+            Err(entry) => {
+                PortPool::free_port(port).await;
+                Err(entry)?
+            }
+        };
         let (data_channel_sender, data_channel_receiver) = DataChannel::new(uuid, socket_stream);
         let mut agent = agent.write().await;
         agent.data_channel_sender = Some(data_channel_sender);
@@ -805,7 +920,7 @@ impl Agent {
     pub async fn terminate(agent: &Arc<RwLock<Agent>>) {
         let uuid = agent.read().await.uuid;
         logging_information!(uuid, SystemEntry::Terminating, "");
-        let inference_task = {
+        {
             let mut agent = agent.write().await;
             agent.control_channel_sender.disconnect().await;
             agent.control_channel_receiver.disconnect().await;
@@ -815,9 +930,7 @@ impl Agent {
             if let Some(data_channel_receiver) = &mut agent.data_channel_receiver {
                 data_channel_receiver.disconnect().await;
             }
-            mem::take(&mut agent.inference_task)
-        };
-        TaskManager::redistribute_task(inference_task).await;
+        }
         AgentManager::remove_agent(uuid).await;
         logging_information!(uuid, SystemEntry::TerminateComplete, "");
     }
@@ -838,7 +951,67 @@ impl Agent {
         self.realtime_usage.clone()
     }
 
-    pub fn inference_tasks(&mut self) -> &mut VecDeque<InferenceTask> {
-        &mut self.inference_task
+    //A backend must run both the model type and the media kind of the subtask.
+    pub fn support_task(&self, inference_task: &InferenceTask) -> bool {
+        let model_type = &inference_task.inference_argument.model_type;
+        self.backends.iter().any(|backend| backend.supports(model_type, inference_task.is_image()))
+    }
+
+    pub fn seconds_per_byte(&self) -> Option<f64> {
+        self.throughput.seconds_per_byte()
+    }
+
+    pub fn pending_work(&self) -> u64 {
+        self.in_flight_task.iter()
+            .map(|inference_task| inference_task.media_size)
+            .sum()
+    }
+
+    fn devices(&self) -> Vec<Option<u32>> {
+        if self.information.gpus.is_empty() {
+            vec![None]
+        } else {
+            self.information.gpus.iter()
+                .map(|gpu_device| Some(gpu_device.index))
+                .collect()
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.concurrency * self.devices().len()
+    }
+
+    pub fn slot_available(&self) -> bool {
+        self.in_flight_task.len() < self.capacity()
+    }
+
+    //Least loaded device whose free VRAM fits the subtask, prefetched subtasks may wait on a device without a free slot.
+    pub fn select_device(&self, estimated_vram: f64, prefetch: bool) -> Option<Option<u32>> {
+        self.devices().into_iter()
+            .map(|device| {
+                let load = self.in_flight_task.iter()
+                    .filter(|inference_task| inference_task.device == device)
+                    .count();
+                (device, load)
+            })
+            .filter(|(_, load)| prefetch || *load < self.concurrency)
+            .filter(|(device, _)| {
+                let free_vram = match device {
+                    Some(index) => self.idle_unused.device_vram(*index).unwrap_or_default(),
+                    None => self.idle_unused.vram,
+                };
+                free_vram >= estimated_vram
+            })
+            .min_by_key(|(_, load)| *load)
+            .map(|(device, _)| device)
+    }
+
+    //Checked against the idle capacity, the same figures pull_task compares a subtask with.
+    pub fn can_accommodate(&self, estimated_vram: f64, estimated_ram: f64) -> bool {
+        self.idle_unused.ram > estimated_ram * 0.7 && self.select_device(estimated_vram, true).is_some()
+    }
+
+    pub fn drain_tasks(&mut self) -> VecDeque<InferenceTask> {
+        self.in_flight_task.drain(..).collect()
     }
 }
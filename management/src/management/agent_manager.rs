@@ -1,5 +1,7 @@
 use crate::management::agent::Agent;
+use crate::management::task_manager::TaskManager;
 use crate::management::utils::agent_information::AgentInformation;
+use crate::management::utils::inference_task::InferenceTask;
 use crate::management::utils::performance::Performance;
 use crate::utils::config::Config;
 use crate::utils::logging::*;
@@ -77,10 +79,20 @@ impl AgentManager {
     }
 
     pub async fn remove_agent(agent_id: Uuid) -> Option<Arc<RwLock<Agent>>> {
-        let mut agent_manager = Self::instance_mut().await;
-        let agent = agent_manager.agents.remove(&agent_id);
-        if agent.is_some() {
-            agent_manager.size -= 1;
+        let agent = {
+            let mut agent_manager = Self::instance_mut().await;
+            let agent = agent_manager.agents.remove(&agent_id);
+            if agent.is_some() {
+                agent_manager.size -= 1;
+            }
+            agent
+        };
+        //Removed first, so none of the in flight tasks are handed back to the same agent.
+        if let Some(agent) = &agent {
+            let inference_tasks = agent.write().await.drain_tasks();
+            if !inference_tasks.is_empty() {
+                TaskManager::redistribute_task(inference_tasks).await;
+            }
         }
         agent
     }
@@ -107,14 +119,21 @@ impl AgentManager {
         agents
     }
 
-    pub async fn filter_agent_by_vram(vram_threshold: f64) -> Vec<(Uuid, f64)> {
+    //Agents that support the model type and could run the subtask once idle, whatever their current load.
+    pub async fn filter_agent_by_capacity(estimated_vram: f64, estimated_ram: f64, inference_task: &InferenceTask) -> Vec<(Uuid, f64)> {
         let agents = Self::sorted_by_vram().await;
-        let mut filtered_agents: Vec<_> = agents.into_iter()
-            .filter(|&(_, agent_vram)| {
-                let vram = if agent_vram.is_nan() { 0.0 } else { agent_vram };
-                vram >= vram_threshold
+        let mut filtered_agents: Vec<_> = stream::iter(agents)
+            .filter(|&(agent_id, _)| async move {
+                match Self::get_agent(agent_id).await {
+                    Some(agent) => {
+                        let agent = agent.read().await;
+                        agent.support_task(inference_task) && agent.can_accommodate(estimated_vram, estimated_ram)
+                    }
+                    None => false,
+                }
             })
-            .collect();
+            .collect()
+            .await;
         filtered_agents.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
         filtered_agents
     }
@@ -140,10 +159,17 @@ impl AgentManager {
         let agent = Self::get_agent(uuid).await?;
         let performance = agent.read().await.realtime_usage();
         let mut agent_manager = Self::instance_mut().await;
-        agent_manager.performance.insert(uuid, (performance, Local::now()));
+        agent_manager.performance.insert(uuid, (performance.clone(), Local::now()));
         Some(performance)
     }
 
+    pub async fn authorize_agents() {
+        let agents: Vec<Arc<RwLock<Agent>>> = Self::instance().await.agents.values().cloned().collect();
+        for agent in agents {
+            Agent::authorize(agent).await;
+        }
+    }
+
     pub async fn size() -> usize {
         Self::instance().await.size
     }
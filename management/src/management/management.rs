@@ -3,9 +3,14 @@ use crate::management::agent::Agent;
 use crate::management::agent_manager::AgentManager;
 use crate::management::media_processor::MediaProcessor;
 use crate::management::monitor::Monitor;
+use crate::management::task_manager::TaskManager;
+use crate::management::task_store::TaskStore;
 use crate::utils::config::Config;
 use crate::utils::logging::*;
-use crate::web::api::{config, default, inference, log, monitor, task};
+use crate::web::api::{auth, config, default, inference, log, monitor, task};
+use crate::web::utils::authentication::authorize;
+use actix_cors::Cors;
+use actix_web::middleware::from_fn;
 use actix_web::{App, HttpServer};
 use lazy_static::lazy_static;
 use std::time::Duration;
@@ -41,23 +46,28 @@ impl Management {
         logging_information!(SystemEntry::Initializing);
         Config::now().await;
         MediaProcessor::run().await;
+        TaskManager::restore().await;
+        TaskManager::run().await;
         Monitor::run().await;
         Self::register_agent().await;
         let http_server = loop {
             let config = Config::now().await;
             let http_server = HttpServer::new(|| {
-                let cors = actix_cors::Cors::default()
-                    .allow_any_origin()
+                let mut cors = Cors::default()
                     .allow_any_method()
                     .allow_any_header()
                     .max_age(3600);
+                for origin in Config::now_blocking().cors_allowed_origins {
+                    cors = if origin == "*" { cors.allow_any_origin() } else { cors.allowed_origin(&origin) };
+                }
                 App::new()
                     .wrap(cors)
-                    .service(config::initialize())
-                    .service(inference::initialize())
-                    .service(log::initialize())
-                    .service(monitor::initialize())
-                    .service(task::initialize())
+                    .service(auth::initialize())
+                    .service(config::initialize().wrap(from_fn(authorize)))
+                    .service(inference::initialize().wrap(from_fn(authorize)))
+                    .service(log::initialize().wrap(from_fn(authorize)))
+                    .service(monitor::initialize().wrap(from_fn(authorize)))
+                    .service(task::initialize().wrap(from_fn(authorize)))
                     .default_service(route().to(default::default_route))
             })
             .bind(format!("0.0.0.0:{}", config.http_server_bind_port));
@@ -83,6 +93,7 @@ impl Management {
         Self::instance_mut().await.terminate = true;
         Monitor::terminate().await;
         MediaProcessor::terminate().await;
+        TaskStore::flush().await;
         logging_information!(SystemEntry::TerminateComplete);
     }
 
@@ -90,16 +101,24 @@ impl Management {
         tokio::spawn(async {
             let mut agent_socket = AgentSocket::new().await;
             while !Self::instance().await.terminate {
-                let id = Uuid::new_v4();
-                let (socket_stream, agent_ip) = agent_socket.get_connection().await;
-                let agent = Agent::new(id, socket_stream).await;
-                match agent {
-                    Ok(agent) => {
-                        AgentManager::add_agent(agent).await;
-                        logging_information!(SystemEntry::AgentConnect(agent_ip));
-                    },
-                    Err(entry) => logging_entry!(id, entry),
-                }
+                let (tcp_stream, agent_ip) = agent_socket.get_connection().await;
+                tokio::spawn(async move {
+                    let id = Uuid::new_v4();
+                    let socket_stream = match AgentSocket::handshake(tcp_stream).await {
+                        Ok(socket_stream) => socket_stream,
+                        Err(entry) => {
+                            logging_entry!(entry);
+                            return;
+                        }
+                    };
+                    match Agent::new(id, socket_stream).await {
+                        Ok(agent) => {
+                            AgentManager::add_agent(agent).await;
+                            logging_information!(SystemEntry::AgentConnect(agent_ip));
+                        },
+                        Err(entry) => logging_entry!(id, entry),
+                    }
+                });
             }
         });
     }
@@ -1,4 +1,6 @@
 use crate::management::task_manager::TaskManager;
+use crate::management::utils::detection::{DetectionResult, MergedFrame};
+use crate::management::utils::detection_file::DetectionFile;
 use crate::management::utils::task::{Task, TaskStatus};
 use crate::management::utils::video_info::VideoInfo;
 use crate::utils::config::{Config, SplitMode};
@@ -8,7 +10,7 @@ use gstreamer::prelude::*;
 use gstreamer_pbutils::prelude::*;
 use gstreamer_pbutils::Discoverer;
 use lazy_static::lazy_static;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::ffi::OsStr;
 use std::fs::File;
 use std::io::{Read, Write};
@@ -23,6 +25,7 @@ use tokio::time::sleep;
 use tokio_stream::wrappers::ReadDirStream;
 use zip::read::ZipArchive;
 use zip::write::SimpleFileOptions;
+use uuid::Uuid;
 use zip::ZipWriter;
 
 lazy_static! {
@@ -34,7 +37,7 @@ pub struct MediaProcessor {
     post_process_tasks: VecDeque<Task>,
     join_handles: Vec<JoinHandle<()>>,
     terminate: bool,
-    cancel_flag: Arc<AtomicBool>,
+    cancel_flags: HashMap<Uuid, Arc<AtomicBool>>,
 }
 
 impl MediaProcessor {
@@ -44,7 +47,7 @@ impl MediaProcessor {
             post_process_tasks: VecDeque::new(),
             join_handles: Vec::new(),
             terminate: false,
-            cancel_flag: Arc::new(AtomicBool::new(false)),
+            cancel_flags: HashMap::new(),
         }
     }
 
@@ -74,7 +77,7 @@ impl MediaProcessor {
         let folders = ["SavedModel", "SavedFile", "PreProcess", "PostProcess", "Result"];
         for &folder_name in &folders {
             let path = PathBuf::from(folder_name);
-            if let Err(err) = fs::create_dir(&path).await {
+            if let Err(err) = fs::create_dir_all(&path).await {
                 logging_critical!(IOEntry::CreateDirectoryError(path.display(), err));
             }
         }
@@ -89,7 +92,9 @@ impl MediaProcessor {
         let handles = {
             let mut instance = Self::instance_mut().await;
             instance.terminate = true;
-            instance.cancel_flag.store(true, Ordering::Relaxed);
+            for cancel_flag in instance.cancel_flags.values() {
+                cancel_flag.store(true, Ordering::Relaxed);
+            }
             std::mem::take(&mut instance.join_handles)
         };
         for handle in handles {
@@ -101,15 +106,32 @@ impl MediaProcessor {
         logging_information!(SystemEntry::TerminateComplete);
     }
 
+    //Scratch folders are kept only for the tasks that are restored on the next start, results stay in place.
     async fn cleanup() {
         logging_information!(SystemEntry::Cleaning);
-        let folders = ["SavedModel", "SavedFile", "PreProcess", "PostProcess", "Result"];
+        let unfinished: HashSet<String> = TaskManager::get_processing_tasks().await.iter()
+            .map(|task| task.uuid.to_string())
+            .collect();
+        let folders = ["PreProcess", "PostProcess"];
         for &folder_name in &folders {
             let path = PathBuf::from(folder_name);
-            if let Err(err) = fs::remove_dir_all(&path).await {
-                logging_error!(IOEntry::DeleteDirectoryError(path.display(), err));
+            let mut folder = match fs::read_dir(&path).await {
+                Ok(folder) => folder,
+                Err(err) => {
+                    logging_error!(IOEntry::ReadDirectoryError(path.display(), err));
+                    continue;
+                }
+            };
+            while let Ok(Some(dir_entry)) = folder.next_entry().await {
+                if unfinished.contains(&dir_entry.file_name().to_string_lossy().to_string()) {
+                    continue;
+                }
+                let path = dir_entry.path();
+                if let Err(err) = fs::remove_dir_all(&path).await {
+                    logging_error!(IOEntry::DeleteDirectoryError(path.display(), err));
+                }
             }
-        };
+        }
         logging_information!(SystemEntry::CleanComplete);
     }
 
@@ -125,10 +147,47 @@ impl MediaProcessor {
         Self::instance_mut().await.post_process_tasks.push_back(task);
     }
 
+    pub async fn cancel_task(uuid: &Uuid) {
+        let mut media_processor = Self::instance_mut().await;
+        media_processor.pre_process_tasks.retain(|task| task.uuid != *uuid);
+        media_processor.post_process_tasks.retain(|task| task.uuid != *uuid);
+        if let Some(cancel_flag) = media_processor.cancel_flags.get(uuid) {
+            cancel_flag.store(true, Ordering::Relaxed);
+        }
+    }
+
+    async fn cancel_flag(uuid: &Uuid) -> Arc<AtomicBool> {
+        let mut media_processor = Self::instance_mut().await;
+        let terminate = media_processor.terminate;
+        media_processor.cancel_flags.entry(*uuid)
+            .or_insert_with(|| Arc::new(AtomicBool::new(terminate)))
+            .clone()
+    }
+
+    async fn pop_task(pre_process: bool) -> Option<Task> {
+        let mut media_processor = Self::instance_mut().await;
+        let task = if pre_process {
+            media_processor.pre_process_tasks.pop_front()
+        } else {
+            media_processor.post_process_tasks.pop_front()
+        };
+        if let Some(task) = &task {
+            media_processor.cancel_flags.insert(task.uuid, Arc::new(AtomicBool::new(false)));
+        }
+        task
+    }
+
+    async fn finish_task(uuid: &Uuid) -> bool {
+        match Self::instance_mut().await.cancel_flags.remove(uuid) {
+            Some(cancel_flag) => cancel_flag.load(Ordering::Relaxed),
+            None => false,
+        }
+    }
+
     async fn pre_process() {
         let config = Config::now().await;
         while !Self::instance().await.terminate {
-            let task = Self::instance_mut().await.pre_process_tasks.pop_front();
+            let task = Self::pop_task(true).await;
             match task {
                 Some(mut task) => {
                     TaskManager::change_task_status(&task.uuid, TaskStatus::PreProcessing).await;
@@ -138,6 +197,9 @@ impl MediaProcessor {
                         Some("zip") => Self::zip_pre_process(&mut task).await,
                         _ => Err(error_entry!(TaskEntry::UnSupportFileType(task.uuid)))
                     };
+                    if Self::finish_task(&task.uuid).await {
+                        continue;
+                    }
                     match result {
                         Ok(_) => TaskManager::distribute_task(task).await,
                         Err(err) => {
@@ -154,7 +216,7 @@ impl MediaProcessor {
     async fn post_process() {
         let config = Config::now().await;
         while !Self::instance().await.terminate {
-            let task = Self::instance_mut().await.post_process_tasks.pop_front();
+            let task = Self::pop_task(false).await;
             match task {
                 Some(mut task) => {
                     TaskManager::change_task_status(&task.uuid, TaskStatus::PostProcessing).await;
@@ -164,6 +226,9 @@ impl MediaProcessor {
                         Some("zip") => Self::zip_post_processing(&mut task).await,
                         _ => Err(error_entry!(TaskEntry::UnSupportFileType(task.uuid)))
                     };
+                    if Self::finish_task(&task.uuid).await {
+                        continue;
+                    }
                     match result {
                         Ok(_) => TaskManager::task_success(&task.uuid).await,
                         Err(err) => {
@@ -238,7 +303,7 @@ impl MediaProcessor {
         Self::prepare_pre_processing(&pre_process_folder, &post_process_folder, &source_path, &destination_path).await?;
         let video_path = destination_path;
         Self::fetch_video_info(&video_path).await?;
-        let cancel_flag = Self::instance().await.cancel_flag.clone();
+        let cancel_flag = Self::cancel_flag(&task.uuid).await;
         Self::split_video(video_path, cancel_flag).await?;
         let count = Self::file_count(&pre_process_folder).await?;
         TaskManager::update_unprocessed(&task.uuid, count).await;
@@ -382,7 +447,7 @@ impl MediaProcessor {
         let destination_path = pre_process_folder.clone().join(&task.media_file_name);
         Self::prepare_pre_processing(&pre_process_folder, &post_process_folder, &source_path, &destination_path).await?;
         let zip_path = destination_path;
-        let cancel_flag = Self::instance().await.cancel_flag.clone();
+        let cancel_flag = Self::cancel_flag(&task.uuid).await;
         Self::unzip(zip_path, cancel_flag).await?;
         let count = Self::file_count(&pre_process_folder).await?;
         TaskManager::update_unprocessed(&task.uuid, count).await;
@@ -429,12 +494,13 @@ impl MediaProcessor {
     }
 
     async fn picture_post_processing(task: &mut Task) -> Result<(), LogEntry> {
+        let uuid = task.uuid.to_string();
         let inference_task = task.result.get(0)
             .ok_or(error_entry!("Missing tasks"))?;
         #[cfg(target_os = "linux")]
-        let source_path = PathBuf::from(format!("./PostProcess/{}", inference_task.media_file_name));
+        let source_path = PathBuf::from(format!("./PostProcess/{}/{}", uuid, inference_task.media_file_name));
         #[cfg(target_os = "windows")]
-        let source_path = PathBuf::from(format!(".\\PostProcess\\{}", inference_task.media_file_name));
+        let source_path = PathBuf::from(format!(".\\PostProcess\\{}\\{}", uuid, inference_task.media_file_name));
         #[cfg(target_os = "linux")]
         let destination_path = PathBuf::from(format!("./Result/{}", inference_task.media_file_name));
         #[cfg(target_os = "windows")]
@@ -442,6 +508,7 @@ impl MediaProcessor {
         fs::rename(&source_path, &destination_path).await
             .map_err(|err|
                 error_entry!(IOEntry::MoveFileError(source_path.display(), destination_path.display(), err)))?;
+        Self::merge_detections(task).await?;
         Ok(())
     }
 
@@ -489,9 +556,10 @@ impl MediaProcessor {
         let video_info_path = post_process_folder.clone().join(&task.media_file_name).with_extension("toml");
         let saved_path = post_process_folder.clone().join(&task.media_file_name);
         Self::prepare_post_processing(media_file_name, &pre_process_folder, &post_process_folder).await?;
-        let cancel_flag = Self::instance().await.cancel_flag.clone();
+        let cancel_flag = Self::cancel_flag(&task.uuid).await;
         Self::recombination_video(video_info_path, post_process_folder, saved_path, cancel_flag).await?;
         Self::move_result(&task).await?;
+        Self::merge_detections(task).await?;
         Ok(())
     }
 
@@ -642,9 +710,10 @@ impl MediaProcessor {
         let post_process_folder = PathBuf::from(format!(".\\PostProcess\\{}", uuid));
         let saved_path = post_process_folder.clone().join(&task.media_file_name);
         Self::prepare_post_processing(media_file_name, &pre_process_folder, &post_process_folder).await?;
-        let cancel_flag = Self::instance().await.cancel_flag.clone();
+        let cancel_flag = Self::cancel_flag(&task.uuid).await;
         Self::recombination_zip(pre_process_folder, saved_path, cancel_flag).await?;
         Self::move_result(&task).await?;
+        Self::merge_detections(task).await?;
         Ok(())
     }
 
@@ -710,6 +779,104 @@ impl MediaProcessor {
         Ok(())
     }
 
+    async fn merge_detections(task: &Task) -> Result<(), LogEntry> {
+        let uuid = task.uuid.to_string();
+        let config = Config::now().await;
+        #[cfg(target_os = "linux")]
+        let post_process_folder = PathBuf::from(format!("./PostProcess/{}", uuid));
+        #[cfg(target_os = "windows")]
+        let post_process_folder = PathBuf::from(format!(".\\PostProcess\\{}", uuid));
+        #[cfg(target_os = "linux")]
+        let detection_path = PathBuf::from(format!("./Result/{}.json", task.media_file_name));
+        #[cfg(target_os = "windows")]
+        let detection_path = PathBuf::from(format!(".\\Result\\{}.json", task.media_file_name));
+        #[cfg(target_os = "linux")]
+        let coco_path = PathBuf::from(format!("./Result/{}.coco.json", task.media_file_name));
+        #[cfg(target_os = "windows")]
+        let coco_path = PathBuf::from(format!(".\\Result\\{}.coco.json", task.media_file_name));
+        let video_info_path = post_process_folder.join(&task.media_file_name).with_extension("toml");
+        let framerate = Self::read_framerate(&video_info_path).await;
+        let mut detection_files = Vec::new();
+        //A segment without detections only loses its own entries, the rendered media is still delivered.
+        for inference_task in task.result.iter().filter(|inference_task| inference_task.error.is_ok()) {
+            let path = post_process_folder.join(format!("{}.json", inference_task.media_file_name));
+            let data = match fs::read(&path).await {
+                Ok(data) => data,
+                Err(err) => {
+                    logging_warning!(IOEntry::ReadFileError(path.display(), err));
+                    continue;
+                }
+            };
+            match serde_json::from_slice::<DetectionFile>(&data) {
+                Ok(detection_file) => detection_files.push((inference_task.media_file_name.clone(), detection_file)),
+                Err(err) => logging_warning!(IOEntry::SerdeDeserializeError(err)),
+            }
+        }
+        let part_frames: BTreeMap<usize, usize> = detection_files.iter()
+            .filter_map(|(source, detection_file)| {
+                Self::sequence_number(source, "Part_").map(|index| (index, detection_file.frame_count))
+            })
+            .collect();
+        let estimated_part_frames = match (config.split_mode, framerate) {
+            (SplitMode::Time { segment_duration_secs }, Some(framerate)) =>
+                (segment_duration_secs as f64 * framerate).round() as usize,
+            _ => 0,
+        };
+        let mut detection_result = DetectionResult::new(task.uuid, task.media_file_name.clone());
+        for (source, detection_file) in detection_files {
+            let frame_offset = if let Some(index) = Self::sequence_number(&source, "Frame_") {
+                index
+            } else if let Some(index) = Self::sequence_number(&source, "Part_") {
+                (0..index).map(|part| part_frames.get(&part).copied().unwrap_or(estimated_part_frames)).sum()
+            } else {
+                0
+            };
+            detection_result.names.extend(detection_file.names);
+            for frame_detection in detection_file.frames {
+                let frame = frame_offset + frame_detection.frame;
+                detection_result.frames.push(MergedFrame {
+                    source: source.clone(),
+                    frame,
+                    timestamp: framerate.map(|framerate| frame as f64 / framerate),
+                    width: detection_file.width,
+                    height: detection_file.height,
+                    detections: frame_detection.detections,
+                });
+            }
+        }
+        detection_result.frames.sort_by(|a, b| a.frame.cmp(&b.frame).then_with(|| a.source.cmp(&b.source)));
+        let detection_data = serde_json::to_vec(&detection_result)
+            .map_err(|err| error_entry!(IOEntry::SerdeSerializeError(err)))?;
+        fs::write(&detection_path, detection_data).await
+            .map_err(|err| error_entry!(IOEntry::WriteFileError(detection_path.display(), err)))?;
+        let coco_data = serde_json::to_vec(&detection_result.as_coco())
+            .map_err(|err| error_entry!(IOEntry::SerdeSerializeError(err)))?;
+        fs::write(&coco_path, coco_data).await
+            .map_err(|err| error_entry!(IOEntry::WriteFileError(coco_path.display(), err)))?;
+        Ok(())
+    }
+
+    async fn read_framerate(video_info_path: &PathBuf) -> Option<f64> {
+        let toml_str = fs::read_to_string(video_info_path).await.ok()?;
+        let video_info: VideoInfo = toml::from_str(&toml_str).ok()?;
+        let (numerator, denominator) = video_info.framerate.split_once('/')?;
+        let numerator = numerator.trim().parse::<f64>().ok()?;
+        let denominator = denominator.trim().parse::<f64>().ok()?;
+        if numerator > 0.0 && denominator > 0.0 {
+            Some(numerator / denominator)
+        } else {
+            None
+        }
+    }
+
+    fn sequence_number(file_name: &str, prefix: &str) -> Option<usize> {
+        file_name.strip_prefix(prefix)?
+            .split('.')
+            .next()?
+            .parse::<usize>()
+            .ok()
+    }
+
     async fn file_count(path: &PathBuf) -> Result<usize, LogEntry> {
         let read_dir = fs::read_dir(path).await
             .map_err(|err| error_entry!(IOEntry::ReadDirectoryError(path.display(), err)))?;
@@ -2,7 +2,9 @@ pub mod utils;
 pub mod agent;
 pub mod agent_manager;
 pub mod media_processor;
+pub mod resource_estimator;
 pub mod management;
 pub mod task_manager;
+pub mod task_store;
 
 pub use common::management::*;
@@ -0,0 +1,190 @@
+use crate::management::utils::inference_task::InferenceTask;
+use crate::management::utils::resource_usage::ResourceUsage;
+use lazy_static::lazy_static;
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+const HISTORY_SIZE: usize = 32;
+
+lazy_static! {
+    static ref RESOURCE_ESTIMATOR: RwLock<ResourceEstimator> = RwLock::new(ResourceEstimator::new());
+}
+
+#[derive(Hash, PartialEq, Eq)]
+struct UsageKey {
+    model: String,
+    imgsz: usize,
+    batch: usize,
+}
+
+impl UsageKey {
+    fn new(inference_task: &InferenceTask) -> Self {
+        let model = if inference_task.model_hash.is_empty() {
+            inference_task.model_file_name.clone()
+        } else {
+            inference_task.model_hash.clone()
+        };
+        Self {
+            model,
+            imgsz: inference_task.inference_argument.imgsz,
+            batch: inference_task.inference_argument.batch,
+        }
+    }
+}
+
+struct UsageSample {
+    media_size: f64, //bytes
+    usage: ResourceUsage,
+}
+
+pub struct ResourceEstimator {
+    history: HashMap<UsageKey, VecDeque<UsageSample>>,
+}
+
+impl ResourceEstimator {
+    fn new() -> Self {
+        Self {
+            history: HashMap::new(),
+        }
+    }
+
+    pub async fn instance() -> RwLockReadGuard<'static, Self> {
+        RESOURCE_ESTIMATOR.read().await
+    }
+
+    pub async fn instance_mut() -> RwLockWriteGuard<'static, Self> {
+        RESOURCE_ESTIMATOR.write().await
+    }
+
+    pub async fn record(inference_task: &InferenceTask, media_size: u64, usage: ResourceUsage) {
+        let mut resource_estimator = Self::instance_mut().await;
+        let samples = resource_estimator.history.entry(UsageKey::new(inference_task)).or_default();
+        if samples.len() == HISTORY_SIZE {
+            samples.pop_front();
+        }
+        samples.push_back(UsageSample { media_size: media_size as f64, usage });
+    }
+
+    //The model is loaded once per subtask, so the largest observed peak is kept as the estimate.
+    //Zero readings come from CPU-only agents or hidden processes and say nothing about GPU agents.
+    pub async fn estimated_vram(inference_task: &InferenceTask) -> Option<f64> {
+        let resource_estimator = Self::instance().await;
+        let samples = resource_estimator.history.get(&UsageKey::new(inference_task))?;
+        samples.iter()
+            .map(|sample| sample.usage.vram)
+            .filter(|vram| *vram > 0.0)
+            .reduce(f64::max)
+    }
+
+    //RAM grows with the decoded media, fit it linearly on the media size once sizes differ.
+    pub async fn estimated_ram(inference_task: &InferenceTask, media_size: u64) -> Option<f64> {
+        let resource_estimator = Self::instance().await;
+        let samples = resource_estimator.history.get(&UsageKey::new(inference_task))?;
+        let peak_ram = samples.iter()
+            .map(|sample| sample.usage.ram)
+            .reduce(f64::max)?;
+        let count = samples.len() as f64;
+        let mean_size = samples.iter().map(|sample| sample.media_size).sum::<f64>() / count;
+        let mean_ram = samples.iter().map(|sample| sample.usage.ram).sum::<f64>() / count;
+        let variance = samples.iter()
+            .map(|sample| (sample.media_size - mean_size).powi(2))
+            .sum::<f64>();
+        if variance == 0.0 {
+            return Some(peak_ram);
+        }
+        let covariance = samples.iter()
+            .map(|sample| (sample.media_size - mean_size) * (sample.usage.ram - mean_ram))
+            .sum::<f64>();
+        let slope = (covariance / variance).max(0.0);
+        let intercept = mean_ram - slope * mean_size;
+        Some((intercept + slope * media_size as f64).max(0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::management::utils::inference_argument::{DetectMode, InferenceArgument, ModelType};
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    //The estimator is shared, so every test uses its own model name.
+    fn inference_task() -> InferenceTask {
+        InferenceTask {
+            task_uuid: Uuid::new_v4(),
+            subtask_uuid: Uuid::new_v4(),
+            model_file_name: Uuid::new_v4().to_string(),
+            model_hash: String::new(),
+            media_file_name: String::new(),
+            model_file_path: PathBuf::new(),
+            media_file_path: PathBuf::new(),
+            media_size: 0,
+            inference_argument: InferenceArgument {
+                model_type: ModelType::Ultralytics,
+                detect_mode: DetectMode::Predict,
+                cache: false,
+                imgsz: 640,
+                batch: 1,
+                conf: 0.25,
+                timeout: None,
+            },
+            error: Ok(()),
+            attempts: Vec::new(),
+            device: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_model_has_no_estimate() {
+        let inference_task = inference_task();
+        assert_eq!(ResourceEstimator::estimated_ram(&inference_task, 1_000).await, None);
+        assert_eq!(ResourceEstimator::estimated_vram(&inference_task).await, None);
+    }
+
+    #[tokio::test]
+    async fn equal_media_sizes_use_the_peak_ram() {
+        let inference_task = inference_task();
+        for ram in [3.0e8, 5.0e8, 4.0e8] {
+            ResourceEstimator::record(&inference_task, 1_000_000, ResourceUsage::new(ram, 0.0)).await;
+        }
+        assert_eq!(ResourceEstimator::estimated_ram(&inference_task, 8_000_000).await, Some(5.0e8));
+    }
+
+    #[tokio::test]
+    async fn ram_grows_linearly_with_media_size() {
+        let inference_task = inference_task();
+        ResourceEstimator::record(&inference_task, 1_000_000, ResourceUsage::new(2.0e8, 0.0)).await;
+        ResourceEstimator::record(&inference_task, 2_000_000, ResourceUsage::new(3.0e8, 0.0)).await;
+        let estimated_ram = ResourceEstimator::estimated_ram(&inference_task, 4_000_000).await.unwrap_or_default();
+        assert!((estimated_ram - 5.0e8).abs() < 1.0);
+    }
+
+    #[tokio::test]
+    async fn ram_never_shrinks_with_media_size() {
+        let inference_task = inference_task();
+        ResourceEstimator::record(&inference_task, 1_000_000, ResourceUsage::new(3.0e8, 0.0)).await;
+        ResourceEstimator::record(&inference_task, 2_000_000, ResourceUsage::new(2.0e8, 0.0)).await;
+        let estimated_ram = ResourceEstimator::estimated_ram(&inference_task, 100_000_000).await.unwrap_or_default();
+        assert!((estimated_ram - 2.5e8).abs() < 1.0);
+    }
+
+    #[tokio::test]
+    async fn zero_vram_samples_are_ignored() {
+        let inference_task = inference_task();
+        ResourceEstimator::record(&inference_task, 1_000_000, ResourceUsage::new(1.0e8, 0.0)).await;
+        assert_eq!(ResourceEstimator::estimated_vram(&inference_task).await, None);
+        ResourceEstimator::record(&inference_task, 1_000_000, ResourceUsage::new(1.0e8, 6.0e8)).await;
+        ResourceEstimator::record(&inference_task, 1_000_000, ResourceUsage::new(1.0e8, 0.0)).await;
+        assert_eq!(ResourceEstimator::estimated_vram(&inference_task).await, Some(6.0e8));
+    }
+
+    #[tokio::test]
+    async fn history_keeps_only_recent_samples() {
+        let inference_task = inference_task();
+        ResourceEstimator::record(&inference_task, 1_000_000, ResourceUsage::new(9.0e8, 0.0)).await;
+        for _ in 0..HISTORY_SIZE {
+            ResourceEstimator::record(&inference_task, 1_000_000, ResourceUsage::new(1.0e8, 0.0)).await;
+        }
+        assert_eq!(ResourceEstimator::estimated_ram(&inference_task, 1_000_000).await, Some(1.0e8));
+    }
+}
@@ -1,16 +1,21 @@
 use crate::management::agent::Agent;
 use crate::management::agent_manager::AgentManager;
 use crate::management::media_processor::MediaProcessor;
+use crate::management::resource_estimator::ResourceEstimator;
+use crate::management::task_store::TaskStore;
+use crate::management::utils::fair_queue::FairQueue;
 use crate::management::utils::inference_task::InferenceTask;
-use crate::management::utils::task::{Task, TaskStatus};
+use crate::management::utils::task::{Task, TaskStatus, DEFAULT_PRIORITY};
 use crate::utils::config::{Config, SplitMode};
 use crate::utils::logging::*;
 use lazy_static::lazy_static;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs;
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tokio::time::sleep;
 use uuid::Uuid;
 
 lazy_static! {
@@ -20,7 +25,9 @@ lazy_static! {
 pub struct TaskManager {
     success: VecDeque<Task>,
     fail: VecDeque<Task>,
+    cancelled: VecDeque<Task>,
     processing: HashMap<Uuid, Task>,
+    pending: FairQueue,
 }
 
 impl TaskManager {
@@ -28,7 +35,9 @@ impl TaskManager {
         Self {
             success: VecDeque::new(),
             fail: VecDeque::new(),
+            cancelled: VecDeque::new(),
             processing: HashMap::new(),
+            pending: FairQueue::default(),
         }
     }
 
@@ -44,9 +53,115 @@ impl TaskManager {
         let mut task_manager = Self::instance_mut().await;
         task_manager.processing.insert(task.uuid, task.clone());
         drop(task_manager);
+        TaskStore::save(&task).await;
         MediaProcessor::add_pre_process_task(task).await;
     }
 
+    pub async fn restore() {
+        let mut unfinished_tasks = VecDeque::new();
+        for task in TaskStore::load().await {
+            let uuid = task.uuid;
+            match task.status {
+                TaskStatus::Success => Self::instance_mut().await.success.push_back(task),
+                TaskStatus::Fail => Self::instance_mut().await.fail.push_back(task),
+                TaskStatus::Cancelled => Self::instance_mut().await.cancelled.push_back(task),
+                TaskStatus::Waiting | TaskStatus::PreProcessing if task.result.is_empty() => {
+                    Self::restore_pre_process(task).await;
+                    logging_information!(TaskEntry::TaskRestored(uuid));
+                }
+                TaskStatus::Processing => {
+                    unfinished_tasks.extend(Self::restore_processing(&task).await);
+                    Self::instance_mut().await.processing.insert(uuid, task);
+                    logging_information!(TaskEntry::TaskRestored(uuid));
+                }
+                TaskStatus::Waiting | TaskStatus::PreProcessing | TaskStatus::PostProcessing => {
+                    Self::instance_mut().await.processing.insert(uuid, task.clone());
+                    MediaProcessor::add_post_process_task(task).await;
+                    logging_information!(TaskEntry::TaskRestored(uuid));
+                }
+            }
+        }
+        if !unfinished_tasks.is_empty() {
+            tokio::spawn(async move {
+                let config = Config::now().await;
+                while AgentManager::size().await == 0 {
+                    sleep(Duration::from_secs(config.refresh_interval)).await;
+                }
+                Self::redistribute_task(unfinished_tasks).await;
+            });
+        }
+    }
+
+    async fn restore_pre_process(mut task: Task) {
+        let uuid = task.uuid.to_string();
+        #[cfg(target_os = "linux")]
+        let source_path = PathBuf::from(format!("./SavedFile/{}", task.media_file_name));
+        #[cfg(target_os = "windows")]
+        let source_path = PathBuf::from(format!(".\\SavedFile\\{}", task.media_file_name));
+        #[cfg(target_os = "linux")]
+        let pre_process_folder = PathBuf::from(format!("./PreProcess/{}", uuid));
+        #[cfg(target_os = "windows")]
+        let pre_process_folder = PathBuf::from(format!(".\\PreProcess\\{}", uuid));
+        #[cfg(target_os = "linux")]
+        let post_process_folder = PathBuf::from(format!("./PostProcess/{}", uuid));
+        #[cfg(target_os = "windows")]
+        let post_process_folder = PathBuf::from(format!(".\\PostProcess\\{}", uuid));
+        let staged_path = pre_process_folder.join(&task.media_file_name);
+        if fs::metadata(&staged_path).await.is_ok()
+            && let Err(err) = fs::rename(&staged_path, &source_path).await {
+            logging_error!(IOEntry::MoveFileError(staged_path.display(), source_path.display(), err));
+        }
+        let _ = fs::remove_dir_all(pre_process_folder).await;
+        let _ = fs::remove_dir_all(post_process_folder).await;
+        task.status = TaskStatus::Waiting;
+        task.failed = 0;
+        task.success = 0;
+        task.unprocessed = 0;
+        Self::add_task(task).await;
+    }
+
+    async fn restore_processing(task: &Task) -> VecDeque<InferenceTask> {
+        let uuid = task.uuid.to_string();
+        #[cfg(target_os = "linux")]
+        let model_file_path = PathBuf::from(format!("./SavedModel/{}", task.model_file_name));
+        #[cfg(target_os = "windows")]
+        let model_file_path = PathBuf::from(format!(".\\SavedModel\\{}", task.model_file_name));
+        #[cfg(target_os = "linux")]
+        let media_folder = PathBuf::from(format!("./PreProcess/{}", uuid));
+        #[cfg(target_os = "windows")]
+        let media_folder = PathBuf::from(format!(".\\PreProcess\\{}", uuid));
+        let media_file_path = media_folder.join(&task.media_file_name);
+        let mut inference_tasks = VecDeque::new();
+        if let Some("png") | Some("jpg") | Some("jpeg") = media_file_path.extension().and_then(|os_str| os_str.to_str()) {
+            if task.result.is_empty() {
+                inference_tasks.push_back(InferenceTask::new(task, model_file_path, media_file_path));
+            }
+            return inference_tasks;
+        }
+        let ignore_file = media_file_path.with_extension("toml");
+        let processed: HashSet<&String> = task.result.iter()
+            .map(|inference_task| &inference_task.media_file_name)
+            .collect();
+        let mut media_folder = match fs::read_dir(&media_folder).await {
+            Ok(media_folder) => media_folder,
+            Err(err) => {
+                logging_error!(IOEntry::ReadDirectoryError(media_folder.display(), err));
+                return inference_tasks;
+            }
+        };
+        while let Ok(Some(dir_entry)) = media_folder.next_entry().await {
+            let path = dir_entry.path();
+            if path == media_file_path || path == ignore_file {
+                continue;
+            }
+            let file_name = dir_entry.file_name().to_string_lossy().to_string();
+            if !processed.contains(&file_name) {
+                inference_tasks.push_back(InferenceTask::new(task, model_file_path.clone(), path));
+            }
+        }
+        inference_tasks
+    }
+
     pub async fn clone_processing_task(uuid: &Uuid) -> Option<Task> {
         let task_manager = Self::instance().await;
         task_manager.processing.get(uuid).cloned()
@@ -86,6 +201,10 @@ impl TaskManager {
         } else {
             return;
         };
+        let task_store = TaskStore::instance_mut().await;
+        drop(task_manager);
+        task_store.insert_task(&task).await;
+        drop(task_store);
         Self::task_cleanup(&task).await;
     }
 
@@ -99,7 +218,46 @@ impl TaskManager {
         } else {
             return;
         };
+        let task_store = TaskStore::instance_mut().await;
+        drop(task_manager);
+        task_store.insert_task(&task).await;
+        drop(task_store);
+        Self::task_cleanup(&task).await;
+    }
+
+    pub async fn get_cancelled_tasks() -> Vec<Task> {
+        let task_manager = Self::instance().await;
+        task_manager.cancelled.iter().cloned().collect()
+    }
+
+    pub async fn cancel_task(uuid: &Uuid) -> bool {
+        let mut task_manager = Self::instance_mut().await;
+        task_manager.pending.remove_task(uuid);
+        let task = if let Some(mut task) = task_manager.processing.remove(uuid) {
+            task.status = TaskStatus::Cancelled;
+            task_manager.cancelled.push_back(task.clone());
+            task
+        } else {
+            return false;
+        };
+        let task_store = TaskStore::instance_mut().await;
+        drop(task_manager);
+        task_store.insert_task(&task).await;
+        drop(task_store);
+        MediaProcessor::cancel_task(uuid).await;
+        for agent_uuid in AgentManager::get_agents_uuid().await {
+            if let Some(agent) = AgentManager::get_agent(agent_uuid).await {
+                Agent::cancel_task(agent, *uuid).await;
+            }
+        }
+        #[cfg(target_os = "linux")]
+        let source_path = PathBuf::from(format!("./SavedFile/{}", task.media_file_name));
+        #[cfg(target_os = "windows")]
+        let source_path = PathBuf::from(format!(".\\SavedFile\\{}", task.media_file_name));
+        let _ = fs::remove_file(source_path).await;
         Self::task_cleanup(&task).await;
+        logging_information!(TaskEntry::TaskCancelled(*uuid));
+        true
     }
 
     pub async fn task_cleanup(task: &Task) {
@@ -125,6 +283,10 @@ impl TaskManager {
         let mut task_manager = Self::instance_mut().await;
         if let Some(task) = task_manager.processing.get_mut(uuid) {
             task.status = status;
+            let task = task.without_result();
+            let task_store = TaskStore::instance_mut().await;
+            drop(task_manager);
+            task_store.insert_task(&task).await;
         }
     }
 
@@ -132,6 +294,10 @@ impl TaskManager {
         let mut task_manager = Self::instance_mut().await;
         if let Some(task) = task_manager.processing.get_mut(uuid) {
             task.unprocessed = unprocessed;
+            let task = task.without_result();
+            let task_store = TaskStore::instance_mut().await;
+            drop(task_manager);
+            task_store.insert_task(&task).await;
         }
     }
 
@@ -158,32 +324,12 @@ impl TaskManager {
         let image_file_path = PathBuf::from(format!("./PreProcess/{}/{}", task_uuid, task.media_file_name));
         #[cfg(target_os = "windows")]
         let image_file_path = PathBuf::from(format!(".\\PreProcess\\{}\\{}", task_uuid, task.media_file_name));
-        let estimated_vram_usage = Self::estimated_vram_usage(&model_file_path).await;
-        let estimated_ram_usage = Self::estimated_ram_usage(&image_file_path).await;
-        let filter_agents = AgentManager::filter_agent_by_vram(estimated_vram_usage).await;
-        let mut inference_task = InferenceTask::new(&task, model_file_path, image_file_path);
-        for (agent_uuid, _) in filter_agents {
-            let ram = AgentManager::get_agent_unused_ram(agent_uuid).await.unwrap_or(0.0);
-            if ram > estimated_ram_usage * 0.7 {
-                if let Some(agent) = AgentManager::get_agent(agent_uuid).await {
-                    if ram < estimated_ram_usage {
-                        inference_task.inference_argument.cache = true;
-                    }
-                    Agent::add_task(agent, inference_task).await;
-                    return;
-                }
-            }
-        }
-        let error_message = TaskEntry::TaskAssignError(task.uuid);
-        inference_task.error = Err(error_message.to_string());
-        logging_warning!(error_message);
-        Self::submit_inference_task(inference_task).await;
+        let inference_task = InferenceTask::new(&task, model_file_path, image_file_path);
+        Self::enqueue(VecDeque::from([inference_task])).await;
     }
 
     pub async fn distribute_video_and_zip(task: Task) {
-        let mut current_agent = 0_usize;
         let uuid = task.uuid.to_string();
-        let config = Config::now().await;
         #[cfg(target_os = "linux")]
         let model_file_path = PathBuf::from(format!("./SavedModel/{}", task.model_file_name));
         #[cfg(target_os = "windows")]
@@ -194,8 +340,6 @@ impl TaskManager {
         let media_folder = PathBuf::from(format!(".\\PreProcess\\{}", uuid));
         let ignore_file1 = media_folder.join(&task.media_file_name);
         let ignore_file2 = ignore_file1.with_extension("toml");
-        let estimated_vram_usage = Self::estimated_vram_usage(&model_file_path).await;
-        let filter_agents = AgentManager::filter_agent_by_vram(estimated_vram_usage).await;
         let mut media_folder = match fs::read_dir(&media_folder).await {
             Ok(media_folder) => media_folder,
             Err(err) => {
@@ -205,113 +349,192 @@ impl TaskManager {
                 return;
             }
         };
-        'outer: while let Ok(Some(dir_entry)) = media_folder.next_entry().await {
+        let mut inference_tasks = VecDeque::new();
+        while let Ok(Some(dir_entry)) = media_folder.next_entry().await {
             let media_file_path = dir_entry.path();
             if media_file_path == ignore_file1 || media_file_path == ignore_file2 {
                 continue;
             }
-            let ram_usage = match config.split_mode {
-                SplitMode::Frame => Self::estimated_ram_usage(&media_file_path).await,
-                SplitMode::Time { .. } => {
-                    let batch_size = task.inference_argument.batch;
-                    Self::estimated_ram_usage(&media_file_path).await * (batch_size as f64)
-                }
-            };
-            let mut inference_task = InferenceTask::new(&task, model_file_path.clone(), media_file_path.clone());
-            for i in 0..filter_agents.len() {
-                let index = (current_agent + i) % filter_agents.len();
-                let agent_uuid = match filter_agents.get(index) {
-                    Some((agent_uuid, _)) => *agent_uuid,
-                    None => continue,
-                };
-                let agent_ram = AgentManager::get_agent_unused_ram(agent_uuid).await.unwrap_or(0.0);
-                if agent_ram > ram_usage * 0.7 {
-                    if let Some(agent) = AgentManager::get_agent(agent_uuid).await {
-                        if agent_ram < ram_usage {
-                            inference_task.inference_argument.cache = true;
-                        }
-                        Agent::add_task(agent, inference_task).await;
-                        current_agent += 1;
-                        continue 'outer;
-                    }
-                }
-            }
-            let error_message = TaskEntry::TaskAssignError(task.uuid);
-            inference_task.error = Err(error_message.to_string());
-            logging_warning!(error_message);
-            Self::submit_inference_task(inference_task).await;
+            inference_tasks.push_back(InferenceTask::new(&task, model_file_path.clone(), media_file_path));
         }
+        Self::enqueue(inference_tasks).await;
     }
 
     pub async fn redistribute_task(inference_tasks: VecDeque<InferenceTask>) {
-        let mut current_agent = 0_usize;
-        'outer: for mut inference_task in inference_tasks {
-            let estimated_ram_usage = TaskManager::estimated_ram_usage(&inference_task.media_file_path).await;
-            let estimated_vram_usage = TaskManager::estimated_vram_usage(&inference_task.model_file_path).await;
-            let filter_agents = AgentManager::filter_agent_by_vram(estimated_vram_usage).await;
-            for i in 0..filter_agents.len() {
-                let index = (current_agent + i) % filter_agents.len();
-                let agent_uuid = match filter_agents.get(index) {
-                    Some((agent_id, _)) => *agent_id,
-                    None => continue,
-                };
-                let ram = AgentManager::get_agent_unused_ram(agent_uuid).await.unwrap_or(0.0);
-                if ram > estimated_ram_usage * 0.7 {
-                    if let Some(agent) = AgentManager::get_agent(agent_uuid).await {
-                        Agent::add_task(agent, inference_task).await;
-                        current_agent += 1;
-                        continue 'outer;
-                    }
-                }
+        Self::enqueue(inference_tasks).await;
+    }
+
+    async fn enqueue(mut inference_tasks: VecDeque<InferenceTask>) {
+        for inference_task in inference_tasks.iter_mut() {
+            if inference_task.media_size == 0 {
+                inference_task.media_size = Self::file_size(&inference_task.media_file_path).await;
+            }
+        }
+        let mut task_manager = Self::instance_mut().await;
+        for inference_task in inference_tasks {
+            let priority = task_manager.processing.get(&inference_task.task_uuid)
+                .map(|task| task.priority)
+                .unwrap_or(DEFAULT_PRIORITY);
+            task_manager.pending.push(priority, inference_task);
+        }
+    }
+
+    pub async fn run() {
+        tokio::spawn(async {
+            loop {
+                Self::sweep_pending().await;
+                sleep(Duration::from_millis(Config::now().await.internal_timestamp)).await;
+            }
+        });
+    }
+
+    //Subtasks wait in the pending queue until an agent pulls them, the ones no connected agent can run are failed here.
+    async fn sweep_pending() {
+        let config = Config::now().await;
+        let exclude_failed_agents = config.retry_policy.exclude_failed_agents;
+        let no_agent = AgentManager::size().await == 0;
+        let heads = Self::instance().await.pending.heads();
+        for inference_task in heads {
+            if !Self::instance().await.processing.contains_key(&inference_task.task_uuid) {
+                Self::instance_mut().await.pending.remove_task(&inference_task.task_uuid);
+                continue;
+            }
+            if no_agent {
+                continue;
+            }
+            let estimated_vram_usage = Self::estimated_vram_usage(&inference_task).await;
+            let estimated_ram_usage = Self::subtask_ram_usage(&config, &inference_task).await;
+            let assignable = AgentManager::filter_agent_by_capacity(estimated_vram_usage, estimated_ram_usage, &inference_task).await.into_iter()
+                .any(|(agent_uuid, _)| !(exclude_failed_agents && inference_task.attempted_on(agent_uuid)));
+            if assignable {
+                continue;
+            }
+            if let Some(mut inference_task) = Self::instance_mut().await.pending.take(inference_task.subtask_uuid) {
+                let error_message = TaskEntry::TaskAssignError(inference_task.task_uuid);
+                inference_task.error = Err(error_message.to_string());
+                logging_warning!(error_message);
+                Self::submit_inference_task(inference_task).await;
             }
-            let error_message = TaskEntry::TaskAssignError(inference_task.task_uuid);
-            inference_task.error = Err(error_message.to_string());
-            logging_warning!(error_message);
-            Self::submit_inference_task(inference_task).await;
         }
     }
 
-    pub async fn steal_task(agent: Arc<RwLock<Agent>>) -> Option<InferenceTask> {
-        let agents = AgentManager::sorted_by_vram().await;
-        let (vram, ram) = {
+    pub async fn pull_task(agent: &Arc<RwLock<Agent>>) -> Option<InferenceTask> {
+        let config = Config::now().await;
+        let fleet_speed = Self::fleet_seconds_per_byte().await.unwrap_or(1.0);
+        let (agent_uuid, ram, prefetch, pending_work, seconds_per_byte) = {
             let agent = agent.read().await;
-            let idle_unused = agent.idle_unused();
-            (idle_unused.vram, idle_unused.ram)
+            let seconds_per_byte = agent.seconds_per_byte().unwrap_or(fleet_speed);
+            (agent.uuid(), agent.idle_unused().ram, !agent.slot_available(), agent.pending_work(), seconds_per_byte)
         };
-        for (agent_uuid, _) in agents {
-            if let Some(agent) = AgentManager::get_agent(agent_uuid).await {
-                let mut steal = false;
-                let mut cache = false;
-                let mut agent = agent.write().await;
-                if let Some(inference_task) = agent.inference_tasks().get(0) {
-                    let estimate_ram = TaskManager::estimated_ram_usage(&inference_task.media_file_path).await;
-                    let estimate_vram = TaskManager::estimated_vram_usage(&inference_task.model_file_path).await;
-                    if ram > estimate_ram * 0.7 && vram > estimate_vram {
-                        steal = true;
-                        if ram < estimate_ram {
-                            cache = true;
-                        }
-                    }
-                }
-                if steal {
-                    if let Some(mut inference_task) = agent.inference_tasks().pop_front() {
-                        inference_task.inference_argument.cache = cache;
-                        return Some(inference_task);
-                    }
-                }
+        let heads = Self::instance().await.pending.heads();
+        for inference_task in heads {
+            if !agent.read().await.support_task(&inference_task)
+                || (config.retry_policy.exclude_failed_agents && inference_task.attempted_on(agent_uuid)) {
+                continue;
+            }
+            let estimated_vram_usage = Self::estimated_vram_usage(&inference_task).await;
+            let estimated_ram_usage = Self::subtask_ram_usage(&config, &inference_task).await;
+            if ram <= estimated_ram_usage * 0.7 {
+                continue;
+            }
+            let Some(device) = agent.read().await.select_device(estimated_vram_usage, prefetch) else {
+                continue;
+            };
+            //Prefetching is left to the agents with a free slot that are expected to finish the subtask earlier.
+            let finish_time = (pending_work + inference_task.media_size) as f64 * seconds_per_byte;
+            let estimated_usage = (estimated_vram_usage, estimated_ram_usage);
+            if prefetch && Self::earlier_agent_available(agent_uuid, &inference_task, estimated_usage, finish_time, fleet_speed).await {
+                continue;
+            }
+            if let Some(mut inference_task) = Self::instance_mut().await.pending.take(inference_task.subtask_uuid) {
+                inference_task.inference_argument.cache = ram < estimated_ram_usage;
+                inference_task.device = device;
+                return Some(inference_task);
             }
         }
         None
     }
 
+    async fn earlier_agent_available(agent_uuid: Uuid, inference_task: &InferenceTask, estimated_usage: (f64, f64), finish_time: f64, fleet_speed: f64) -> bool {
+        let config = Config::now().await;
+        let exclude_failed_agents = config.retry_policy.exclude_failed_agents;
+        let (estimated_vram_usage, estimated_ram_usage) = estimated_usage;
+        for (other_uuid, _) in AgentManager::filter_agent_by_capacity(estimated_vram_usage, estimated_ram_usage, inference_task).await {
+            if other_uuid == agent_uuid || (exclude_failed_agents && inference_task.attempted_on(other_uuid)) {
+                continue;
+            }
+            if let Some(agent) = AgentManager::get_agent(other_uuid).await {
+                let agent = agent.read().await;
+                let seconds_per_byte = agent.seconds_per_byte().unwrap_or(fleet_speed);
+                let other_finish_time = (agent.pending_work() + inference_task.media_size) as f64 * seconds_per_byte;
+                if agent.slot_available() && other_finish_time < finish_time {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    async fn fleet_seconds_per_byte() -> Option<f64> {
+        let mut speeds = Vec::new();
+        for agent_uuid in AgentManager::get_agents_uuid().await {
+            if let Some(agent) = AgentManager::get_agent(agent_uuid).await
+                && let Some(seconds_per_byte) = agent.read().await.seconds_per_byte() {
+                speeds.push(seconds_per_byte);
+            }
+        }
+        if speeds.is_empty() {
+            None
+        } else {
+            Some(speeds.iter().sum::<f64>() / speeds.len() as f64)
+        }
+    }
+
+    async fn subtask_ram_usage(config: &Config, inference_task: &InferenceTask) -> f64 {
+        let ram_usage = Self::estimated_ram_usage(inference_task).await;
+        match (&config.split_mode, inference_task.media_file_path.extension().and_then(|os_str| os_str.to_str())) {
+            (_, Some("png") | Some("jpg") | Some("jpeg")) | (SplitMode::Frame, _) => ram_usage,
+            (SplitMode::Time { .. }, _) => ram_usage * (inference_task.inference_argument.batch as f64),
+        }
+    }
+
+    pub async fn retry_inference_task(mut inference_task: InferenceTask) {
+        let retry_policy = Config::now().await.retry_policy;
+        let attempts = inference_task.attempts.len();
+        let processing = Self::instance().await.processing.contains_key(&inference_task.task_uuid);
+        if attempts >= retry_policy.max_attempts || !processing {
+            Self::submit_inference_task(inference_task).await;
+            return;
+        }
+        let backoff = retry_policy.backoff.saturating_mul(2_u64.saturating_pow(attempts.saturating_sub(1) as u32));
+        logging_information!(TaskEntry::SubtaskRetry(inference_task.task_uuid, attempts + 1));
+        inference_task.error = Ok(());
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(backoff)).await;
+            Self::redistribute_task(VecDeque::from([inference_task])).await;
+        });
+    }
+
     pub async fn submit_inference_task(inference_task: InferenceTask) {
         let uuid = inference_task.task_uuid;
         let mut task_manager = Self::instance_mut().await;
+        let cancelled = task_manager.cancelled.iter().any(|task| task.uuid == uuid);
         match task_manager.processing.get_mut(&uuid) {
             Some(task) => {
+                //A subtask submitted twice would be counted twice and leave the counters inconsistent.
+                let duplicated = task.result.iter().any(|result| result.subtask_uuid == inference_task.subtask_uuid);
+                let unprocessed = match task.unprocessed.checked_sub(1) {
+                    Some(unprocessed) if !duplicated => unprocessed,
+                    _ => {
+                        drop(task_manager);
+                        logging_warning!(TaskEntry::DuplicateSubtaskResult(uuid, inference_task.subtask_uuid));
+                        return;
+                    }
+                };
                 let success = inference_task.error.is_ok();
-                task.unprocessed -= 1;
-                task.result.push(inference_task);
+                let index = task.result.len();
+                task.unprocessed = unprocessed;
+                task.result.push(inference_task.clone());
                 if success {
                     task.success += 1;
                 } else {
@@ -321,30 +544,42 @@ impl TaskManager {
                     task.status = TaskStatus::Waiting;
                     MediaProcessor::add_post_process_task(task.clone()).await;
                 }
+                //Only the new result and the small task record are written, outside the task manager lock.
+                let task = task.without_result();
+                let task_store = TaskStore::instance_mut().await;
+                drop(task_manager);
+                task_store.insert_result(&uuid, index, &inference_task).await;
+                task_store.insert_task(&task).await;
             }
+            None if cancelled => {},
             None => logging_error!(TaskEntry::TaskDoesNotExist(uuid)),
         }
     }
 
-    pub async fn estimated_vram_usage(model_file_path: &PathBuf) -> f64 {
-        let model_filesize = match fs::metadata(model_file_path).await {
-            Ok(metadata) => metadata.len(),
-            Err(err) => {
-                logging_error!(IOEntry::ReadFileError(model_file_path.display(), err));
-                0
-            }
-        };
+    //Usage measured by agents is preferred, the regression constants only cover models never run before.
+    pub async fn estimated_vram_usage(inference_task: &InferenceTask) -> f64 {
+        if let Some(vram_usage) = ResourceEstimator::estimated_vram(inference_task).await {
+            return vram_usage;
+        }
+        let model_filesize = Self::file_size(&inference_task.model_file_path).await;
         2.4319e-6 * model_filesize as f64 + 303.3889
     }
 
-    pub async fn estimated_ram_usage(media_file_path: &PathBuf) -> f64 {
-        let media_filesize = match fs::metadata(media_file_path).await {
+    pub async fn estimated_ram_usage(inference_task: &InferenceTask) -> f64 {
+        let media_filesize = Self::file_size(&inference_task.media_file_path).await;
+        if let Some(ram_usage) = ResourceEstimator::estimated_ram(inference_task, media_filesize).await {
+            return ram_usage;
+        }
+        4.1894 * media_filesize as f64 + 1_398_237_298.688
+    }
+
+    pub async fn file_size(file_path: &PathBuf) -> u64 {
+        match fs::metadata(file_path).await {
             Ok(metadata) => metadata.len(),
             Err(err) => {
-                logging_error!(IOEntry::ReadFileError(media_file_path.display(), err));
+                logging_error!(IOEntry::ReadFileError(file_path.display(), err));
                 0
             }
-        };
-        4.1894 * media_filesize as f64 + 1_398_237_298.688
+        }
     }
 }
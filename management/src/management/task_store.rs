@@ -0,0 +1,146 @@
+use crate::management::utils::inference_task::InferenceTask;
+use crate::management::utils::task::Task;
+use crate::utils::logging::*;
+use lazy_static::lazy_static;
+use sled::{Db, Tree};
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use uuid::Uuid;
+
+lazy_static! {
+    static ref TASK_STORE: RwLock<TaskStore> = RwLock::new(TaskStore::new());
+}
+
+pub struct TaskStore {
+    database: Option<Db>,
+    results: Option<Tree>, //subtask results keyed by task uuid and result index
+}
+
+impl TaskStore {
+    fn new() -> Self {
+        #[cfg(target_os = "linux")]
+        let database_path = "./TaskStore";
+        #[cfg(target_os = "windows")]
+        let database_path = ".\\TaskStore";
+        let database = match sled::open(database_path) {
+            Ok(database) => Some(database),
+            Err(err) => {
+                logging_console!(critical_entry!(IOEntry::TaskStoreError(err.to_string())));
+                None
+            }
+        };
+        let results = database.as_ref().and_then(|database| match database.open_tree("result") {
+            Ok(results) => Some(results),
+            Err(err) => {
+                logging_console!(critical_entry!(IOEntry::TaskStoreError(err.to_string())));
+                None
+            }
+        });
+        Self {
+            database,
+            results,
+        }
+    }
+
+    pub async fn instance() -> RwLockReadGuard<'static, Self> {
+        TASK_STORE.read().await
+    }
+
+    //Writers take the write guard before releasing the task manager lock, so records land in the order they changed.
+    pub async fn instance_mut() -> RwLockWriteGuard<'static, Self> {
+        TASK_STORE.write().await
+    }
+
+    pub async fn save(task: &Task) {
+        Self::instance_mut().await.insert_task(task).await;
+    }
+
+    //The task record is written without its results, they are stored once each by insert_result.
+    pub async fn insert_task(&self, task: &Task) {
+        if let Some(database) = &self.database {
+            let task_data = match serde_json::to_vec(&task.without_result()) {
+                Ok(task_data) => task_data,
+                Err(err) => {
+                    logging_error!(IOEntry::SerdeSerializeError(err));
+                    return;
+                }
+            };
+            if let Err(err) = database.insert(task.uuid.as_bytes(), task_data) {
+                logging_error!(IOEntry::TaskStoreError(err.to_string()));
+            }
+        }
+    }
+
+    pub async fn insert_result(&self, task_uuid: &Uuid, index: usize, inference_task: &InferenceTask) {
+        if let Some(results) = &self.results {
+            let result_data = match serde_json::to_vec(inference_task) {
+                Ok(result_data) => result_data,
+                Err(err) => {
+                    logging_error!(IOEntry::SerdeSerializeError(err));
+                    return;
+                }
+            };
+            if let Err(err) = results.insert(Self::result_key(task_uuid, index), result_data) {
+                logging_error!(IOEntry::TaskStoreError(err.to_string()));
+            }
+        }
+    }
+
+    fn result_key(task_uuid: &Uuid, index: usize) -> Vec<u8> {
+        let mut key = task_uuid.as_bytes().to_vec();
+        key.extend_from_slice(&(index as u64).to_be_bytes());
+        key
+    }
+
+    pub async fn load() -> Vec<Task> {
+        let task_store = Self::instance().await;
+        let mut tasks = Vec::new();
+        if let Some(database) = &task_store.database {
+            for record in database.iter() {
+                let task_data = match record {
+                    Ok((_, task_data)) => task_data,
+                    Err(err) => {
+                        logging_error!(IOEntry::TaskStoreError(err.to_string()));
+                        continue;
+                    }
+                };
+                match serde_json::from_slice::<Task>(&task_data) {
+                    Ok(mut task) => {
+                        task.result.extend(task_store.load_result(&task.uuid).await);
+                        tasks.push(task);
+                    },
+                    Err(err) => logging_error!(IOEntry::SerdeDeserializeError(err)),
+                }
+            }
+        }
+        tasks
+    }
+
+    //Big endian indexes keep the prefix scan in submission order.
+    async fn load_result(&self, task_uuid: &Uuid) -> Vec<InferenceTask> {
+        let mut inference_tasks = Vec::new();
+        if let Some(results) = &self.results {
+            for record in results.scan_prefix(task_uuid.as_bytes()) {
+                let result_data = match record {
+                    Ok((_, result_data)) => result_data,
+                    Err(err) => {
+                        logging_error!(IOEntry::TaskStoreError(err.to_string()));
+                        continue;
+                    }
+                };
+                match serde_json::from_slice::<InferenceTask>(&result_data) {
+                    Ok(inference_task) => inference_tasks.push(inference_task),
+                    Err(err) => logging_error!(IOEntry::SerdeDeserializeError(err)),
+                }
+            }
+        }
+        inference_tasks
+    }
+
+    pub async fn flush() {
+        let task_store = Self::instance().await;
+        if let Some(database) = &task_store.database
+            && let Err(err) = database.flush_async().await {
+            logging_error!(IOEntry::TaskStoreError(err.to_string()));
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::management::utils::detection_file::Detection;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MergedFrame {
+    pub source: String,
+    pub frame: usize,
+    pub timestamp: Option<f64>,
+    pub width: u32,
+    pub height: u32,
+    pub detections: Vec<Detection>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DetectionResult {
+    pub task_uuid: Uuid,
+    pub media_file_name: String,
+    pub names: BTreeMap<u32, String>,
+    pub frames: Vec<MergedFrame>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CocoImage {
+    pub id: usize,
+    pub file_name: String,
+    pub width: u32,
+    pub height: u32,
+    pub frame_index: usize,
+    pub timestamp: Option<f64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CocoAnnotation {
+    pub id: usize,
+    pub image_id: usize,
+    pub category_id: u32,
+    pub bbox: [f64; 4],
+    pub area: f64,
+    pub score: f64,
+    pub iscrowd: u8,
+    pub track_id: Option<i64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CocoCategory {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CocoExport {
+    pub images: Vec<CocoImage>,
+    pub annotations: Vec<CocoAnnotation>,
+    pub categories: Vec<CocoCategory>,
+}
+
+impl DetectionResult {
+    pub fn new(task_uuid: Uuid, media_file_name: String) -> Self {
+        Self {
+            task_uuid,
+            media_file_name,
+            names: BTreeMap::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn as_coco(&self) -> CocoExport {
+        let mut images = Vec::new();
+        let mut annotations = Vec::new();
+        for (image_id, frame) in self.frames.iter().enumerate() {
+            images.push(CocoImage {
+                id: image_id,
+                file_name: frame.source.clone(),
+                width: frame.width,
+                height: frame.height,
+                frame_index: frame.frame,
+                timestamp: frame.timestamp,
+            });
+            for detection in &frame.detections {
+                let [x1, y1, x2, y2] = detection.bbox;
+                let (width, height) = (x2 - x1, y2 - y1);
+                annotations.push(CocoAnnotation {
+                    id: annotations.len(),
+                    image_id,
+                    category_id: detection.class_id,
+                    bbox: [x1, y1, width, height],
+                    area: width * height,
+                    score: detection.confidence,
+                    iscrowd: 0,
+                    track_id: detection.track_id,
+                });
+            }
+        }
+        let categories = self.names.iter()
+            .map(|(id, name)| CocoCategory { id: *id, name: name.clone() })
+            .collect();
+        CocoExport {
+            images,
+            annotations,
+            categories,
+        }
+    }
+}
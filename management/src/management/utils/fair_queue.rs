@@ -0,0 +1,161 @@
+use crate::management::utils::inference_task::InferenceTask;
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+struct Flow {
+    task_uuid: Uuid,
+    last_finish: f64,
+    subtasks: VecDeque<(f64, InferenceTask)>,
+}
+
+//Weighted fair queue over tasks, every subtask costs one unit of work divided by its task's weight.
+#[derive(Default)]
+pub struct FairQueue {
+    virtual_time: f64,
+    flows: Vec<Flow>,
+}
+
+impl FairQueue {
+    pub fn push(&mut self, weight: u32, inference_task: InferenceTask) {
+        let task_uuid = inference_task.task_uuid;
+        let index = match self.flows.iter().position(|flow| flow.task_uuid == task_uuid) {
+            Some(index) => index,
+            None => {
+                self.flows.push(Flow { task_uuid, last_finish: self.virtual_time, subtasks: VecDeque::new() });
+                self.flows.len() - 1
+            }
+        };
+        let flow = &mut self.flows[index];
+        let finish = flow.last_finish.max(self.virtual_time) + 1.0 / weight.max(1) as f64;
+        flow.last_finish = finish;
+        flow.subtasks.push_back((finish, inference_task));
+    }
+
+    //Next subtask of every task in service order, each agent takes the first one it is able to run.
+    pub fn heads(&self) -> Vec<InferenceTask> {
+        let mut heads: Vec<&(f64, InferenceTask)> = self.flows.iter()
+            .filter_map(|flow| flow.subtasks.front())
+            .collect();
+        heads.sort_by(|a, b| a.0.total_cmp(&b.0));
+        heads.into_iter()
+            .map(|(_, inference_task)| inference_task.clone())
+            .collect()
+    }
+
+    pub fn take(&mut self, subtask_uuid: Uuid) -> Option<InferenceTask> {
+        let index = self.flows.iter()
+            .position(|flow| flow.subtasks.front().is_some_and(|(_, inference_task)| inference_task.subtask_uuid == subtask_uuid))?;
+        let (finish, inference_task) = self.flows[index].subtasks.pop_front()?;
+        if self.flows[index].subtasks.is_empty() {
+            self.flows.remove(index);
+        }
+        self.virtual_time = self.virtual_time.max(finish);
+        Some(inference_task)
+    }
+
+    pub fn remove_task(&mut self, task_uuid: &Uuid) {
+        self.flows.retain(|flow| flow.task_uuid != *task_uuid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::management::utils::inference_argument::{DetectMode, InferenceArgument, ModelType};
+    use std::path::PathBuf;
+
+    fn inference_task(task_uuid: Uuid) -> InferenceTask {
+        InferenceTask {
+            task_uuid,
+            subtask_uuid: Uuid::new_v4(),
+            model_file_name: String::new(),
+            model_hash: String::new(),
+            media_file_name: String::new(),
+            model_file_path: PathBuf::new(),
+            media_file_path: PathBuf::new(),
+            media_size: 0,
+            inference_argument: InferenceArgument {
+                model_type: ModelType::Ultralytics,
+                detect_mode: DetectMode::Predict,
+                cache: false,
+                imgsz: 640,
+                batch: 1,
+                conf: 0.25,
+                timeout: None,
+            },
+            error: Ok(()),
+            attempts: Vec::new(),
+            device: None,
+        }
+    }
+
+    fn serve(fair_queue: &mut FairQueue, count: usize) -> Vec<Uuid> {
+        (0..count)
+            .filter_map(|_| {
+                let head = fair_queue.heads().into_iter().next()?;
+                fair_queue.take(head.subtask_uuid).map(|inference_task| inference_task.task_uuid)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn heavier_task_is_served_in_proportion_to_its_weight() {
+        let (heavy, light) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut fair_queue = FairQueue::default();
+        for _ in 0..8 {
+            fair_queue.push(2, inference_task(heavy));
+            fair_queue.push(1, inference_task(light));
+        }
+        let served = serve(&mut fair_queue, 6);
+        assert_eq!(served.iter().filter(|task_uuid| **task_uuid == heavy).count(), 4);
+        assert_eq!(served.iter().filter(|task_uuid| **task_uuid == light).count(), 2);
+    }
+
+    #[test]
+    fn late_task_gets_no_credit_for_time_it_was_absent() {
+        let (early, late) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut fair_queue = FairQueue::default();
+        for _ in 0..6 {
+            fair_queue.push(1, inference_task(early));
+        }
+        serve(&mut fair_queue, 4);
+        for _ in 0..2 {
+            fair_queue.push(1, inference_task(late));
+        }
+        assert_eq!(serve(&mut fair_queue, 4), vec![early, late, early, late]);
+    }
+
+    #[test]
+    fn zero_weight_is_treated_as_one() {
+        let (zero, one) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut fair_queue = FairQueue::default();
+        for _ in 0..2 {
+            fair_queue.push(0, inference_task(zero));
+            fair_queue.push(1, inference_task(one));
+        }
+        assert_eq!(serve(&mut fair_queue, 4), vec![zero, one, zero, one]);
+    }
+
+    #[test]
+    fn only_queue_heads_can_be_taken() {
+        let task_uuid = Uuid::new_v4();
+        let mut fair_queue = FairQueue::default();
+        let (first, second) = (inference_task(task_uuid), inference_task(task_uuid));
+        fair_queue.push(1, first.clone());
+        fair_queue.push(1, second.clone());
+        assert!(fair_queue.take(second.subtask_uuid).is_none());
+        assert!(fair_queue.take(first.subtask_uuid).is_some());
+        assert!(fair_queue.take(second.subtask_uuid).is_some());
+        assert!(fair_queue.heads().is_empty());
+    }
+
+    #[test]
+    fn removed_task_leaves_the_queue() {
+        let (removed, kept) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut fair_queue = FairQueue::default();
+        fair_queue.push(1, inference_task(removed));
+        fair_queue.push(1, inference_task(kept));
+        fair_queue.remove_task(&removed);
+        assert_eq!(serve(&mut fair_queue, 2), vec![kept]);
+    }
+}
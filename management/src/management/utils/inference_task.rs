@@ -1,19 +1,30 @@
 use crate::management::utils::inference_argument::InferenceArgument;
 use crate::management::utils::task::Task;
+use crate::management::utils::task_attempt::TaskAttempt;
 use crate::management::utils::task_info::TaskInfo;
 use std::path::PathBuf;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InferenceTask {
     pub task_uuid: Uuid,
+    #[serde(default = "Uuid::new_v4")]
+    pub subtask_uuid: Uuid,
     pub model_file_name: String,
+    #[serde(default)]
+    pub model_hash: String,
     pub media_file_name: String,
     pub model_file_path: PathBuf,
     pub media_file_path: PathBuf,
+    #[serde(default)]
+    pub media_size: u64, //bytes
     pub inference_argument: InferenceArgument,
     pub error: Result<(), String>,
+    #[serde(default)]
+    pub attempts: Vec<TaskAttempt>,
+    #[serde(default)]
+    pub device: Option<u32>,
 }
 
 impl InferenceTask {
@@ -23,16 +34,37 @@ impl InferenceTask {
             .unwrap_or_default().to_string();
         Self {
             task_uuid: task.uuid,
+            subtask_uuid: Uuid::new_v4(),
             model_file_name: task.model_file_name.clone(),
+            model_hash: task.model_hash.clone(),
             media_file_name: image_file_name,
             model_file_path,
             media_file_path,
+            media_size: 0,
             inference_argument: task.inference_argument.clone(),
-            error: Ok(())
+            error: Ok(()),
+            attempts: Vec::new(),
+            device: None,
         }
     }
 
-    pub fn as_task_info(&self) -> TaskInfo {
-        TaskInfo::new(self.task_uuid, self.model_file_name.clone(), self.media_file_name.clone(), self.inference_argument.clone())
+    pub fn record_failure(&mut self, agent_uuid: Uuid, error: String) {
+        self.attempts.push(TaskAttempt::new(agent_uuid, error.clone()));
+        self.error = Err(error);
+    }
+
+    //Frames and pictures are images, everything else is a video segment.
+    pub fn is_image(&self) -> bool {
+        matches!(self.media_file_path.extension().and_then(|os_str| os_str.to_str()), Some("png") | Some("jpg") | Some("jpeg"))
+    }
+
+    pub fn attempted_on(&self, agent_uuid: Uuid) -> bool {
+        self.attempts.iter().any(|attempt| attempt.agent_uuid == agent_uuid)
+    }
+
+    pub fn as_task_info(&self, estimated_vram: f64) -> TaskInfo {
+        let mut task_info = TaskInfo::new(self.task_uuid, self.subtask_uuid, self.model_file_name.clone(), self.model_hash.clone(), self.media_file_name.clone(), self.inference_argument.clone(), estimated_vram);
+        task_info.device = self.device;
+        task_info
     }
 }
@@ -1,5 +1,9 @@
+pub mod detection;
+pub mod fair_queue;
 pub mod inference_task;
 pub mod task;
+pub mod task_attempt;
+pub mod throughput;
 pub mod video_info;
 
 pub use common::management::utils::*;
@@ -1,9 +1,15 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use crate::management::utils::file_reader::FileReader;
 use crate::management::utils::inference_argument::InferenceArgument;
 use crate::management::utils::inference_task::InferenceTask;
+use crate::utils::logging::*;
+use std::path::PathBuf;
 use uuid::Uuid;
 
-#[derive(Serialize, Debug, Copy, Clone)]
+pub const DEFAULT_PRIORITY: u32 = 1;
+pub const MAX_PRIORITY: u32 = 100;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
 pub enum TaskStatus {
     Waiting,
     PreProcessing,
@@ -11,9 +17,10 @@ pub enum TaskStatus {
     PostProcessing,
     Success,
     Fail,
+    Cancelled,
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Task {
     pub uuid: Uuid,
     pub status: TaskStatus,
@@ -21,14 +28,19 @@ pub struct Task {
     pub success: usize,
     pub unprocessed: usize,
     pub model_file_name: String,
+    #[serde(default)]
+    pub model_hash: String,
     pub media_file_name: String,
     pub inference_argument: InferenceArgument,
+    #[serde(default = "Task::default_priority")]
+    pub priority: u32, //weight in fair scheduling, 1 to MAX_PRIORITY
     pub result: Vec<InferenceTask>,
     pub error: Result<(), String>,
 }
 
 impl Task {
-    pub async fn new(uuid: Uuid, model_file_name: String, media_file_name: String, inference_argument: InferenceArgument) -> Self {
+    pub async fn new(uuid: Uuid, model_file_name: String, media_file_name: String, inference_argument: InferenceArgument, priority: u32) -> Self {
+        let model_hash = Self::model_hash(&model_file_name).await;
         Self {
             uuid,
             status: TaskStatus::Waiting,
@@ -36,10 +48,48 @@ impl Task {
             success: 0_usize,
             unprocessed: 0_usize,
             model_file_name,
+            model_hash,
             media_file_name,
             inference_argument,
+            priority,
             result: Vec::new(),
             error: Ok(()),
         }
     }
+
+    //Subtask results are persisted on their own, so storing a task does not serialize all of them again.
+    pub fn without_result(&self) -> Self {
+        Self {
+            uuid: self.uuid,
+            status: self.status,
+            failed: self.failed,
+            success: self.success,
+            unprocessed: self.unprocessed,
+            model_file_name: self.model_file_name.clone(),
+            model_hash: self.model_hash.clone(),
+            media_file_name: self.media_file_name.clone(),
+            inference_argument: self.inference_argument.clone(),
+            priority: self.priority,
+            result: Vec::new(),
+            error: self.error.clone(),
+        }
+    }
+
+    fn default_priority() -> u32 {
+        DEFAULT_PRIORITY
+    }
+
+    async fn model_hash(model_file_name: &String) -> String {
+        #[cfg(target_os = "linux")]
+        let model_file_path = PathBuf::from(format!("./SavedModel/{}", model_file_name));
+        #[cfg(target_os = "windows")]
+        let model_file_path = PathBuf::from(format!(".\\SavedModel\\{}", model_file_name));
+        match FileReader::sha256_of(&model_file_path).await {
+            Ok(sha256) => sha256,
+            Err(entry) => {
+                logging_entry!(entry);
+                String::new()
+            }
+        }
+    }
 }
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TaskAttempt {
+    pub agent_uuid: Uuid,
+    pub error: String,
+}
+
+impl TaskAttempt {
+    pub fn new(agent_uuid: Uuid, error: String) -> Self {
+        Self {
+            agent_uuid,
+            error,
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+const SMOOTHING_FACTOR: f64 = 0.3;
+
+#[derive(Default)]
+pub struct Throughput {
+    seconds_per_byte: Option<f64>,
+    started: HashMap<Uuid, Instant>,
+    last_completed: Option<Instant>,
+}
+
+impl Throughput {
+    pub fn start(&mut self, subtask_uuid: Uuid) {
+        self.started.insert(subtask_uuid, Instant::now());
+    }
+
+    //Subtasks overlap on an agent, so only the time since the previous completion is charged to this one.
+    pub fn complete(&mut self, subtask_uuid: Uuid, work: u64) {
+        let Some(started) = self.started.remove(&subtask_uuid) else {
+            return;
+        };
+        let now = Instant::now();
+        let begin = match self.last_completed {
+            Some(last_completed) if last_completed > started => last_completed,
+            _ => started,
+        };
+        self.last_completed = Some(now);
+        let sample = (now - begin).as_secs_f64() / work.max(1) as f64;
+        self.seconds_per_byte = match self.seconds_per_byte {
+            Some(seconds_per_byte) => Some(SMOOTHING_FACTOR * sample + (1.0 - SMOOTHING_FACTOR) * seconds_per_byte),
+            None => Some(sample),
+        };
+    }
+
+    pub fn forget(&mut self, subtask_uuid: Uuid) {
+        self.started.remove(&subtask_uuid);
+    }
+
+    pub fn seconds_per_byte(&self) -> Option<f64> {
+        self.seconds_per_byte
+    }
+}
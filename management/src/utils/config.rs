@@ -1,8 +1,13 @@
+use crate::connection::socket::tls::TlsConfig;
 use crate::utils::logging::*;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::Path;
+use subtle::{Choice, ConstantTimeEq};
+use tokio::fs as async_fs;
 use tokio::sync::RwLock as AsyncRwLock;
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Value};
 use std::sync::RwLock as SyncRwLock;
 
 lazy_static! {
@@ -19,6 +24,110 @@ pub enum SplitMode {
     },
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AgentAuthentication {
+    pub enable: bool,
+    #[serde(default)]
+    pub allowed_tokens: Vec<String>,
+    #[serde(default)]
+    pub revoked_tokens: Vec<String>,
+}
+
+impl AgentAuthentication {
+    pub fn authorize(&self, token: &str) -> Result<(), String> {
+        if !self.enable {
+            return Ok(());
+        }
+        if Self::contains(&self.revoked_tokens, token) {
+            return Err("Token has been revoked.".to_string());
+        }
+        if !Self::contains(&self.allowed_tokens, token) {
+            return Err("Token is not allowed.".to_string());
+        }
+        Ok(())
+    }
+
+    //Every entry is compared in constant time, so the response time does not reveal how much of a token matched.
+    fn contains(tokens: &[String], token: &str) -> bool {
+        tokens.iter()
+            .fold(Choice::from(0), |found, candidate| found | candidate.as_bytes().ct_eq(token.as_bytes()))
+            .into()
+    }
+
+    fn as_inline_table(&self) -> InlineTable {
+        let mut inline_table = InlineTable::new();
+        inline_table.insert("enable", Value::from(self.enable));
+        inline_table.insert("allowed_tokens", Value::Array(self.allowed_tokens.iter().map(String::as_str).collect::<Array>()));
+        inline_table.insert("revoked_tokens", Value::Array(self.revoked_tokens.iter().map(String::as_str).collect::<Array>()));
+        inline_table
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    Admin,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiKey {
+    pub key: String,
+    pub role: Role,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebUser {
+    pub username: String,
+    pub password_hash: String, //argon2 PHC string
+    pub role: Role,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebAuthentication {
+    pub enable: bool,
+    #[serde(default = "WebAuthentication::default_session_duration")]
+    pub session_duration: u64, //seconds
+    #[serde(default)]
+    pub api_keys: Vec<ApiKey>,
+    #[serde(default)]
+    pub users: Vec<WebUser>,
+}
+
+impl WebAuthentication {
+    fn default_session_duration() -> u64 {
+        3600
+    }
+}
+
+impl Default for WebAuthentication {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            session_duration: Self::default_session_duration(),
+            api_keys: Vec::new(),
+            users: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: usize, //attempts including the first one
+    pub backoff: u64, //milliseconds, doubled after every failed attempt
+    pub exclude_failed_agents: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff: 1000,
+            exclude_failed_agents: true,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct ConfigTable {
     #[serde(rename = "Config")]
@@ -39,6 +148,18 @@ pub struct Config {
     pub control_channel_timeout: u64, //seconds
     pub data_channel_timeout: u64, //seconds
     pub file_transfer_timeout: u64, //seconds
+    #[serde(default)]
+    pub cors_allowed_origins: Vec<String>, //"*" allows any origin
+    #[serde(default)]
+    pub web_authentication: WebAuthentication, //http api keys and users
+    #[serde(default)]
+    pub agent_authentication: AgentAuthentication, //agent token allow/revoke list
+    #[serde(default)]
+    pub tls: TlsConfig, //tls settings
+    #[serde(default)]
+    pub agent_prefetch_depth: usize, //tasks transferred ahead of the agent's concurrency
+    #[serde(default)]
+    pub retry_policy: RetryPolicy, //subtask retry on agent failure
 }
 
 impl Config {
@@ -76,9 +197,43 @@ impl Config {
         SYNC_CONFIG.read().unwrap().clone()
     }
 
-    pub async fn update(config: Config) {
+    //Changed token lists are written to management.toml like allow and revoke do, the other settings stay in memory.
+    //The async lock is taken first, so both copies always end up with the same update.
+    pub async fn update_and_save(config: Config) -> Result<(), LogEntry> {
+        let mut async_config = ASYNC_CONFIG.write().await;
+        if async_config.agent_authentication != config.agent_authentication {
+            Self::save_agent_authentication(&config.agent_authentication).await?;
+        }
         *SYNC_CONFIG.write().unwrap() = config.clone();
-        *ASYNC_CONFIG.write().await = config;
+        *async_config = config;
+        Ok(())
+    }
+
+    //The lock is held from the read to the update, so concurrent changes cannot overwrite each other and the file matches memory.
+    pub async fn update_agent_authentication(modify: impl FnOnce(&mut AgentAuthentication)) -> Result<(), LogEntry> {
+        let mut async_config = ASYNC_CONFIG.write().await;
+        let mut agent_authentication = async_config.agent_authentication.clone();
+        modify(&mut agent_authentication);
+        Self::save_agent_authentication(&agent_authentication).await?;
+        async_config.agent_authentication = agent_authentication;
+        *SYNC_CONFIG.write().unwrap() = async_config.clone();
+        Ok(())
+    }
+
+    //Only the token lists are rewritten, the rest of management.toml and its comments are kept as they are.
+    async fn save_agent_authentication(agent_authentication: &AgentAuthentication) -> Result<(), LogEntry> {
+        let config_path = Path::new("./management.toml");
+        let toml_string = async_fs::read_to_string(config_path).await
+            .map_err(|err| error_entry!(IOEntry::ReadFileError(config_path.display(), err)))?;
+        let mut document = toml_string.parse::<DocumentMut>()
+            .map_err(|_| error_entry!(SystemEntry::InvalidConfig))?;
+        let mut value = Value::InlineTable(agent_authentication.as_inline_table());
+        if let Some(previous) = document["Config"]["agent_authentication"].as_value() {
+            *value.decor_mut() = previous.decor().clone();
+        }
+        document["Config"]["agent_authentication"] = Item::Value(value);
+        async_fs::write(config_path, document.to_string()).await
+            .map_err(|err| error_entry!(IOEntry::WriteFileError(config_path.display(), err)))
     }
 
     pub fn validate(config: &Config) -> bool {
@@ -91,6 +246,11 @@ impl Config {
             && Config::validate_second(config.control_channel_timeout)
             && Config::validate_second(config.data_channel_timeout)
             && Config::validate_second(config.file_transfer_timeout)
+            && Config::validate_origins(&config.cors_allowed_origins)
+            && config.web_authentication.session_duration > 0
+            && config.tls.validate_server()
+            && config.retry_policy.max_attempts > 0
+            && Config::validate_mini_second(config.retry_policy.backoff)
     }
 
     fn validate_mini_second(second: u64) -> bool {
@@ -101,6 +261,10 @@ impl Config {
         second <= 3600
     }
 
+    fn validate_origins(origins: &[String]) -> bool {
+        origins.iter().all(|origin| origin == "*" || origin.starts_with("http://") || origin.starts_with("https://"))
+    }
+
     fn validate_port_range(port: [u16; 2]) -> bool {
         let (start, end) = match (port.get(0), port.get(1)) {
             (Some(start), Some(end)) => (*start, *end),
@@ -0,0 +1,69 @@
+use crate::utils::config::{Config, Role};
+use crate::web::utils::authentication::{extract_token, get_role};
+use crate::web::utils::session::SessionManager;
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder, Scope};
+use argon2::password_hash::{PasswordHash, SaltString};
+use argon2::{Argon2, PasswordHasher, PasswordVerifier};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+lazy_static! {
+    //Verified when the username is unknown, so a login takes as long whether or not the user exists.
+    static ref DUMMY_PASSWORD_HASH: String = SaltString::from_b64("VmlzaW9HcmlkRHVtbXlTYWx0")
+        .and_then(|salt| Argon2::default().hash_password(b"", &salt).map(|hash| hash.to_string()))
+        .unwrap_or_default();
+}
+
+#[derive(Deserialize)]
+struct Credential {
+    username: String,
+    password: String,
+}
+
+pub fn initialize() -> Scope {
+    web::scope("/auth")
+        .service(login)
+        .service(logout)
+        .service(ticket)
+}
+
+#[post("/login")]
+async fn login(credential: web::Json<Credential>) -> impl Responder {
+    let config = Config::now().await;
+    let credential = credential.into_inner();
+    let user = config.web_authentication.users.iter()
+        .find(|user| user.username == credential.username);
+    let password_hash = user.map_or(DUMMY_PASSWORD_HASH.as_str(), |user| user.password_hash.as_str());
+    let verified = PasswordHash::new(password_hash).ok()
+        .is_some_and(|hash| Argon2::default().verify_password(credential.password.as_bytes(), &hash).is_ok());
+    match user {
+        Some(user) if verified => {
+            let session = SessionManager::create_session(user.username.clone(), user.role, config.web_authentication.session_duration).await;
+            HttpResponse::Ok().json(web::Json(session))
+        },
+        _ => HttpResponse::Unauthorized().body("Invalid username or password."),
+    }
+}
+
+#[post("/logout")]
+async fn logout(req: HttpRequest) -> impl Responder {
+    match extract_token(&req) {
+        Some(token) if SessionManager::remove_session(&token).await => HttpResponse::Ok().finish(),
+        _ => HttpResponse::Unauthorized().finish(),
+    }
+}
+
+//Issues a ticket for the websocket and download routes, carrying the role of the caller.
+#[post("/ticket")]
+async fn ticket(req: HttpRequest) -> impl Responder {
+    let config = Config::now().await;
+    let role = match extract_token(&req) {
+        Some(token) => get_role(&config, &token).await,
+        None if !config.web_authentication.enable => Some(Role::Admin),
+        None => None,
+    };
+    match role {
+        Some(role) => HttpResponse::Ok().json(web::Json(SessionManager::create_ticket(role).await)),
+        None => HttpResponse::Unauthorized().finish(),
+    }
+}
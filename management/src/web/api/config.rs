@@ -1,10 +1,21 @@
+use crate::management::agent_manager::AgentManager;
 use crate::utils::config::Config;
+use crate::utils::logging::*;
 use actix_web::{get, post, web, HttpResponse, Responder, Scope};
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Deserialize)]
+struct AgentToken {
+    token: String,
+}
 
 pub fn initialize() -> Scope {
     web::scope("/config")
         .service(get_config)
         .service(update_config)
+        .service(allow_agent_token)
+        .service(revoke_agent_token)
 }
 
 #[get("/get")]
@@ -12,13 +23,67 @@ async fn get_config() -> impl Responder {
     web::Json(Config::now().await)
 }
 
+//Authentication sections are required here, a missing one would otherwise default to disabled.
 #[post("/update")]
-async fn update_config(config: web::Json<Config>) -> impl Responder {
+async fn update_config(config: web::Json<Value>) -> impl Responder {
     let config = config.into_inner();
+    if config.get("web_authentication").is_none() || config.get("agent_authentication").is_none() {
+        return HttpResponse::BadRequest().body("web_authentication and agent_authentication are required.");
+    }
+    let config = match serde_json::from_value::<Config>(config) {
+        Ok(config) => config,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid configuration."),
+    };
     if Config::validate(&config) {
-        Config::update(config).await;
-        HttpResponse::Ok().finish()
+        let result = Config::update_and_save(config).await;
+        if result.is_ok() {
+            AgentManager::authorize_agents().await;
+        }
+        save_result(result).await
     } else {
         HttpResponse::BadRequest().body("Invalid configuration.")
     }
 }
+
+#[post("/agent_token/allow")]
+async fn allow_agent_token(agent_token: web::Json<AgentToken>) -> impl Responder {
+    let token = agent_token.into_inner().token;
+    if token.is_empty() {
+        return HttpResponse::BadRequest().body("Invalid token.");
+    }
+    let result = Config::update_agent_authentication(|authentication| {
+        authentication.revoked_tokens.retain(|revoked| *revoked != token);
+        if !authentication.allowed_tokens.contains(&token) {
+            authentication.allowed_tokens.push(token);
+        }
+    }).await;
+    save_result(result).await
+}
+
+#[post("/agent_token/revoke")]
+async fn revoke_agent_token(agent_token: web::Json<AgentToken>) -> impl Responder {
+    let token = agent_token.into_inner().token;
+    if token.is_empty() {
+        return HttpResponse::BadRequest().body("Invalid token.");
+    }
+    let result = Config::update_agent_authentication(|authentication| {
+        authentication.allowed_tokens.retain(|allowed| *allowed != token);
+        if !authentication.revoked_tokens.contains(&token) {
+            authentication.revoked_tokens.push(token);
+        }
+    }).await;
+    if result.is_ok() {
+        AgentManager::authorize_agents().await;
+    }
+    save_result(result).await
+}
+
+async fn save_result(result: Result<(), LogEntry>) -> HttpResponse {
+    match result {
+        Ok(_) => HttpResponse::Ok().finish(),
+        Err(entry) => {
+            logging_entry!(entry);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
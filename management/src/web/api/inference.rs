@@ -1,12 +1,14 @@
 use crate::management::task_manager::TaskManager;
-use crate::management::utils::inference_argument::InferenceArgument;
-use crate::management::utils::task::Task;
+use crate::management::utils::inference_argument::{InferenceArgument, ModelType};
+use crate::management::utils::task::{Task, DEFAULT_PRIORITY, MAX_PRIORITY};
+use crate::utils::config::{Config, SplitMode};
 use actix_multipart::{Field, Multipart};
 use actix_web::http::header::ContentDisposition;
 use actix_web::{post, web, HttpResponse, Responder, Scope};
 use futures::{StreamExt, TryStreamExt};
 use sanitize_filename::sanitize;
 use std::path::{Path, PathBuf};
+use tokio::fs;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
@@ -20,8 +22,10 @@ pub fn initialize() -> Scope {
 async fn save_files(mut payload: Multipart) -> impl Responder {
     let uuid = Uuid::new_v4();
     let mut inference_argument = None;
+    let mut priority = DEFAULT_PRIORITY;
     let mut model_file_name = String::new();
     let mut media_file_name = String::new();
+    let mut saved_files = Vec::new();
     while let Ok(Some(mut field)) = payload.try_next().await {
         let content_disposition = match field.content_disposition() {
             Some(content_disposition) => content_disposition,
@@ -30,6 +34,11 @@ async fn save_files(mut payload: Multipart) -> impl Responder {
         if let Some(field_name) = get_field_name(&content_disposition) {
             if field_name == "inferenceArgument" {
                 inference_argument = parse_inference_argument(&mut field).await;
+            } else if field_name == "priority" {
+                match parse_priority(&mut field).await {
+                    Some(value) => priority = value,
+                    None => return HttpResponse::BadRequest().body(format!("Priority must be between 1 and {}.", MAX_PRIORITY)),
+                }
             } else {
                 if let Some(mut file_name) = get_file_name(&content_disposition) {
                     let sanitized_file_name = sanitize(file_name);
@@ -40,7 +49,8 @@ async fn save_files(mut payload: Multipart) -> impl Responder {
                     let file_extension = Path::new(&file_name).extension()
                         .and_then(|os_str| os_str.to_str()).unwrap_or("");
                     let save_path = match (&*field_name, file_extension) {
-                        ("modelFile", "pt" | "pth" | "onnx") => {
+                        //The model extension depends on the model type, it is checked once the whole payload is read.
+                        ("modelFile", _) => {
                             model_file_name = file_name.clone();
                             #[cfg(target_os = "linux")]
                             { PathBuf::from(format!("./SavedModel/{}", file_name)) }
@@ -59,6 +69,7 @@ async fn save_files(mut payload: Multipart) -> impl Responder {
                     if create_file(&save_path, &mut field).await.is_err() {
                         return HttpResponse::InternalServerError().finish();
                     }
+                    saved_files.push(save_path);
                 } else {
                     return HttpResponse::BadRequest().body("Invalid payload.")
                 }
@@ -72,11 +83,42 @@ async fn save_files(mut payload: Multipart) -> impl Responder {
     }
     // Have checked above
     let inference_argument = inference_argument.unwrap();
-    let new_task = Task::new(uuid, model_file_name, media_file_name, inference_argument).await;
+    if let Err(reason) = validate_files(&inference_argument, &model_file_name, &media_file_name).await {
+        remove_files(&saved_files).await;
+        return HttpResponse::BadRequest().body(reason);
+    }
+    let new_task = Task::new(uuid, model_file_name, media_file_name, inference_argument, priority).await;
     TaskManager::add_task(new_task).await;
     HttpResponse::Ok().finish()
 }
 
+async fn validate_files(inference_argument: &InferenceArgument, model_file_name: &str, media_file_name: &str) -> Result<(), &'static str> {
+    let model_extension = Path::new(model_file_name).extension().and_then(|os_str| os_str.to_str()).unwrap_or("");
+    let model_allowed = match inference_argument.model_type {
+        ModelType::Onnx => model_extension == "onnx",
+        ModelType::Custom(_) => true, //external commands accept formats such as .engine or .tflite
+        ModelType::YOLOv4 => matches!(model_extension, "pt" | "pth" | "weights"),
+        _ => matches!(model_extension, "pt" | "pth" | "onnx"),
+    };
+    if !model_allowed {
+        return Err("Invalid model file extension for the model type.");
+    }
+    if inference_argument.model_type == ModelType::Onnx {
+        //The ONNX backend only runs pictures, videos must be split into frames to reach it.
+        let video = matches!(Path::new(media_file_name).extension().and_then(|os_str| os_str.to_str()), Some("mp4" | "avi" | "mkv"));
+        if video && matches!(Config::now().await.split_mode, SplitMode::Time { .. }) {
+            return Err("Onnx model type cannot run video segments, use the frame split mode.");
+        }
+    }
+    Ok(())
+}
+
+async fn remove_files(file_paths: &[PathBuf]) {
+    for file_path in file_paths {
+        let _ = fs::remove_file(file_path).await;
+    }
+}
+
 fn get_field_name(content_disposition: &ContentDisposition) -> Option<String> {
     match content_disposition.get_name() {
         Some(field_name) => Some(field_name.to_string()),
@@ -101,6 +143,16 @@ async fn parse_inference_argument(field: &mut Field) -> Option<InferenceArgument
     serde_json::from_str(&json_str).ok()
 }
 
+async fn parse_priority(field: &mut Field) -> Option<u32> {
+    let mut data = Vec::new();
+    while let Some(chunk) = field.next().await {
+        let bytes = chunk.ok()?;
+        data.extend_from_slice(&bytes);
+    }
+    let priority = String::from_utf8_lossy(&data).trim().parse::<u32>().ok()?;
+    (1..=MAX_PRIORITY).contains(&priority).then_some(priority)
+}
+
 async fn create_file(save_path: &PathBuf, field: &mut Field) -> Result<(), ()>{
     let mut file = File::create(&save_path).await.map_err(|_| ())?;
     while let Some(chunk) = field.next().await {
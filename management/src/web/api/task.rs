@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 use actix_files::NamedFile;
-use actix_web::{get, web, HttpResponse, Responder, Scope, HttpRequest};
+use actix_web::{get, post, web, HttpResponse, Responder, Scope, HttpRequest};
 use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
 use uuid::Uuid;
 use crate::management::task_manager::TaskManager;
@@ -10,7 +10,11 @@ pub fn initialize() -> Scope {
         .service(processing_tasks)
         .service(success_tasks)
         .service(failed_tasks)
+        .service(cancelled_tasks)
+        .service(cancel_task)
         .service(download_result)
+        .service(download_detection)
+        .service(download_coco)
 }
 
 #[get("/processing_tasks")]
@@ -31,31 +35,63 @@ async fn failed_tasks() -> impl Responder {
     HttpResponse::Ok().json(web::Json(tasks))
 }
 
+#[get("/cancelled_tasks")]
+async fn cancelled_tasks() -> impl Responder {
+    let tasks = TaskManager::get_cancelled_tasks().await;
+    HttpResponse::Ok().json(web::Json(tasks))
+}
+
+#[post("/cancel/{uuid}")]
+async fn cancel_task(uuid: web::Path<Uuid>) -> impl Responder {
+    if TaskManager::cancel_task(&uuid.into_inner()).await {
+        HttpResponse::Ok().finish()
+    } else {
+        HttpResponse::NotFound().finish()
+    }
+}
+
 #[get("/download/{uuid}")]
 async fn download_result(req: HttpRequest, uuid: web::Path<Uuid>) -> impl Responder {
-    let result = TaskManager::clone_success_task(&uuid.into_inner()).await;
-    match result {
-        Some(task) => {
-            #[cfg(target_os = "linux")]
-            let file_path = PathBuf::from(format!("./Result/{}", task.media_file_name));
-            #[cfg(target_os = "windows")]
-            let file_path = PathBuf::from(format!(".\\Result\\{}", task.media_file_name));
-            match NamedFile::open_async(&file_path).await {
-                Ok(named_file) => {
-                    let cd = ContentDisposition {
-                        disposition: DispositionType::Attachment,
-                        parameters: vec![
-                            DispositionParam::Filename(task.media_file_name.clone()),
-                        ],
-                    };
-                    named_file
-                        .set_content_disposition(cd)
-                        .set_content_type(mime_guess::from_path(&file_path).first_or_octet_stream())
-                        .into_response(&req)
-                }
-                Err(_) => HttpResponse::NotFound().finish()
-            }
-        }
+    match TaskManager::clone_success_task(&uuid.into_inner()).await {
+        Some(task) => download_file(&req, task.media_file_name).await,
         None => HttpResponse::NotFound().finish(),
     }
 }
+
+#[get("/detection/{uuid}")]
+async fn download_detection(req: HttpRequest, uuid: web::Path<Uuid>) -> impl Responder {
+    match TaskManager::clone_success_task(&uuid.into_inner()).await {
+        Some(task) => download_file(&req, format!("{}.json", task.media_file_name)).await,
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+#[get("/coco/{uuid}")]
+async fn download_coco(req: HttpRequest, uuid: web::Path<Uuid>) -> impl Responder {
+    match TaskManager::clone_success_task(&uuid.into_inner()).await {
+        Some(task) => download_file(&req, format!("{}.coco.json", task.media_file_name)).await,
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+async fn download_file(req: &HttpRequest, file_name: String) -> HttpResponse {
+    #[cfg(target_os = "linux")]
+    let file_path = PathBuf::from(format!("./Result/{}", file_name));
+    #[cfg(target_os = "windows")]
+    let file_path = PathBuf::from(format!(".\\Result\\{}", file_name));
+    match NamedFile::open_async(&file_path).await {
+        Ok(named_file) => {
+            let cd = ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![
+                    DispositionParam::Filename(file_name),
+                ],
+            };
+            named_file
+                .set_content_disposition(cd)
+                .set_content_type(mime_guess::from_path(&file_path).first_or_octet_stream())
+                .into_response(req)
+        }
+        Err(_) => HttpResponse::NotFound().finish()
+    }
+}
@@ -0,0 +1,66 @@
+use crate::utils::config::{Config, Role};
+use crate::web::utils::session::SessionManager;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::web::Query;
+use actix_web::{Error, HttpRequest, HttpResponse};
+use std::collections::HashMap;
+use subtle::ConstantTimeEq;
+
+pub async fn authorize(req: ServiceRequest, next: Next<impl MessageBody>) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let config = Config::now().await;
+    if !config.web_authentication.enable {
+        return Ok(next.call(req).await?.map_into_left_body());
+    }
+    // Configuration may contain credentials, so reading it requires the admin role as well.
+    let required_role = if req.method() == Method::GET && !req.path().starts_with("/config") {
+        Role::Viewer
+    } else {
+        Role::Admin
+    };
+    let role = match (extract_token(req.request()), extract_ticket(req.request())) {
+        (Some(token), _) => get_role(&config, &token).await,
+        (None, Some(ticket)) => SessionManager::redeem_ticket(&ticket).await,
+        (None, None) => None,
+    };
+    match role {
+        Some(role) if role >= required_role => Ok(next.call(req).await?.map_into_left_body()),
+        Some(_) => Ok(req.into_response(HttpResponse::Forbidden().finish()).map_into_right_body()),
+        None => Ok(req.into_response(HttpResponse::Unauthorized().finish()).map_into_right_body()),
+    }
+}
+
+//Credentials are only taken from the Authorization header, query strings end up in access logs and browser history.
+pub fn extract_token(req: &HttpRequest) -> Option<String> {
+    req.headers().get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+}
+
+//Websocket handshakes and plain download links cannot carry headers, those routes take a single use ticket instead.
+fn extract_ticket(req: &HttpRequest) -> Option<String> {
+    let ticket_routes = ["/monitor/websocket/", "/task/download/", "/task/detection/", "/task/coco/"];
+    if req.method() != Method::GET || !ticket_routes.iter().any(|route| req.path().starts_with(route)) {
+        return None;
+    }
+    Query::<HashMap<String, String>>::from_query(req.query_string()).ok()?
+        .remove("ticket")
+}
+
+//Every API key is compared in constant time, like the agent tokens.
+pub async fn get_role(config: &Config, token: &str) -> Option<Role> {
+    let mut role = None;
+    for api_key in &config.web_authentication.api_keys {
+        if bool::from(api_key.key.as_bytes().ct_eq(token.as_bytes())) {
+            role = Some(api_key.role);
+        }
+    }
+    match role {
+        Some(role) => Some(role),
+        None => SessionManager::get_role(token).await,
+    }
+}
@@ -1,2 +1,4 @@
+pub mod authentication;
 pub mod response;
 pub mod performance_websocket;
+pub mod session;
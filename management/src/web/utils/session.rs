@@ -0,0 +1,106 @@
+use crate::utils::config::Role;
+use lazy_static::lazy_static;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tokio::time::{Duration, Instant};
+
+const TICKET_DURATION: u64 = 30; //seconds
+
+lazy_static! {
+    static ref SESSION_MANAGER: RwLock<SessionManager> = RwLock::new(SessionManager::new());
+}
+
+#[derive(Serialize, Clone)]
+pub struct Session {
+    pub token: String,
+    pub username: String,
+    pub role: Role,
+    pub expires_in: u64, //seconds
+    #[serde(skip)]
+    expire_at: Instant,
+}
+
+#[derive(Serialize, Clone)]
+pub struct Ticket {
+    pub ticket: String,
+    pub expires_in: u64, //seconds
+}
+
+pub struct SessionManager {
+    sessions: HashMap<String, Session>,
+    tickets: HashMap<String, (Role, Instant)>, //single use, for requests a browser cannot add headers to
+}
+
+impl SessionManager {
+    fn new() -> Self {
+        Self {
+            sessions: HashMap::new(),
+            tickets: HashMap::new(),
+        }
+    }
+
+    pub async fn instance() -> RwLockReadGuard<'static, Self> {
+        SESSION_MANAGER.read().await
+    }
+
+    pub async fn instance_mut() -> RwLockWriteGuard<'static, Self> {
+        SESSION_MANAGER.write().await
+    }
+
+    fn random_token() -> String {
+        rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(48)
+            .map(char::from)
+            .collect()
+    }
+
+    pub async fn create_session(username: String, role: Role, duration: u64) -> Session {
+        let token = Self::random_token();
+        let session = Session {
+            token: token.clone(),
+            username,
+            role,
+            expires_in: duration,
+            expire_at: Instant::now() + Duration::from_secs(duration),
+        };
+        let mut session_manager = Self::instance_mut().await;
+        let now = Instant::now();
+        session_manager.sessions.retain(|_, session| session.expire_at > now);
+        session_manager.sessions.insert(token, session.clone());
+        session
+    }
+
+    pub async fn get_role(token: &str) -> Option<Role> {
+        let session_manager = Self::instance().await;
+        session_manager.sessions.get(token)
+            .filter(|session| session.expire_at > Instant::now())
+            .map(|session| session.role)
+    }
+
+    pub async fn remove_session(token: &str) -> bool {
+        Self::instance_mut().await.sessions.remove(token).is_some()
+    }
+
+    pub async fn create_ticket(role: Role) -> Ticket {
+        let ticket = Self::random_token();
+        let mut session_manager = Self::instance_mut().await;
+        let now = Instant::now();
+        session_manager.tickets.retain(|_, (_, expire_at)| *expire_at > now);
+        session_manager.tickets.insert(ticket.clone(), (role, now + Duration::from_secs(TICKET_DURATION)));
+        Ticket {
+            ticket,
+            expires_in: TICKET_DURATION,
+        }
+    }
+
+    //A ticket is removed on its first use, whether or not it has expired.
+    pub async fn redeem_ticket(ticket: &str) -> Option<Role> {
+        Self::instance_mut().await.tickets.remove(ticket)
+            .filter(|(_, expire_at)| *expire_at > Instant::now())
+            .map(|(role, _)| role)
+    }
+}